@@ -19,10 +19,18 @@ import {
 } from './module/erc20Transfer';
 import { buildSwapSignatures_rs } from './module/swap';
 import { buildMintNFTSignatures } from './module/mint';
+import { buildWorkloadSignatures_rs } from './module/workload';
 import { EVMMonitor } from '../monitor/service/web3/EVMMonitor';
 import { Block } from 'web3-types';
 import { ethers } from 'ethers';
-import { Receipt, rsSendRawTransactions } from 'rs-addon';
+import {
+  Receipt,
+  SendFailure,
+  SendOptions,
+  rsLoadKeyring,
+  rsSaveKeyring,
+  rsSendRawTransactions,
+} from 'rs-addon';
 import {
   BenchmarkResult,
   BuildTxFunc,
@@ -34,7 +42,7 @@ import { createUniSwapLP } from './module/swap';
 import { EvmType, getNetwork, RateControlType, TxType } from '../lib/config';
 import { prepareMinting } from './module/mint';
 import { delay } from '../monitor/utils/delay';
-import { waitForKeypress } from '../generator/utils/utils';
+import { isSendFailure, waitForKeypress } from '../generator/utils/utils';
 import {
   prepareDespositBenchmarkConfig,
   zkSyncBridgeDeposit,
@@ -90,8 +98,14 @@ export class AppService {
 
     const network = this.network;
     const monitor = new EVMMonitor(network.node_url);
+    const keyring = config.account.keyring;
 
     try {
+      if (keyring && fs.existsSync(keyring.path)) {
+        const loaded = await rsLoadKeyring(keyring.path, keyring.password);
+        Logger.log(`[keyring] loaded ${loaded} keys from ${keyring.path}`);
+      }
+
       switch (config.tx_type) {
         case TxType.ERC20: {
           await fundNativeTokensRs();
@@ -115,6 +129,7 @@ export class AppService {
           break;
         }
         case TxType.NORMAL:
+        case TxType.WORKLOAD:
           await fundNativeTokensRs();
           break;
         case TxType.DEPOSIT:
@@ -124,6 +139,11 @@ export class AppService {
           break;
       }
 
+      if (keyring) {
+        const saved = await rsSaveKeyring(keyring.path, keyring.password);
+        Logger.log(`[keyring] saved ${saved} keys to ${keyring.path}`);
+      }
+
       // fetch timestamp on latest block
       this.beginningBlockHeight = await monitor.getBlockNumber();
       this.syncNodeTimestampDiff(this.beginningBlockHeight);
@@ -172,7 +192,7 @@ export class AppService {
     const endIndex = startIndex + txSending - 1;
     const network = this.network;
 
-    let txResults: (Error | Receipt)[] = [];
+    let txResults: (Error | SendFailure | Receipt)[] = [];
 
     if (config.tx_type !== TxType.DEPOSIT) {
       Logger.log(
@@ -190,7 +210,11 @@ export class AppService {
 
       this.currentAccountIndex += txSending;
 
-      txResults = await rsSendRawTransactions(network.node_url, signedTxs);
+      txResults = await rsSendRawTransactions(
+        network.node_url,
+        signedTxs,
+        this.sendOptions(testRound, signedTxs.length),
+      );
     } else {
       this.currentAccountIndex += txSending;
       txResults = await zkSyncBridgeDeposit(
@@ -205,7 +229,7 @@ export class AppService {
 
     const result: TransactionResult[] = txResults.map((r) => {
       // error
-      if ('name' in r) {
+      if (r instanceof Error || isSendFailure(r)) {
         Logger.error(`Transaction Error:`, r);
         return {
           receipt: null,
@@ -332,6 +356,20 @@ export class AppService {
     };
   }
 
+  // spreads the sends of a round over `every_n_second` in the addon, rather than sending them all
+  // at once. `maximum-rate` ramps up from the rate of the previous round, which is a step lower
+  sendOptions(testRound: number, txCount: number): SendOptions {
+    const { type, every_n_second } = config.rate_control;
+    const previousTxCount = this.txRoundArray[testRound - 2] ?? txCount;
+    return {
+      rateControl: {
+        mode: type,
+        tps: txCount / every_n_second,
+        startTps: previousTxCount / every_n_second,
+      },
+    };
+  }
+
   async startNewRoundRevised(
     testRound: number,
     warmUpRound: number,
@@ -350,13 +388,17 @@ export class AppService {
 
     this.currentAccountIndex += txSending;
 
-    const txResults = await rsSendRawTransactions(network.node_url, signedTxs);
+    const txResults = await rsSendRawTransactions(
+      network.node_url,
+      signedTxs,
+      this.sendOptions(testRound, signedTxs.length),
+    );
     InfluxDBConnector.writePoints(points);
     Logger.log(`Send transactions done | ${txResults.length} transactions`);
 
     const result: TransactionResult[] = txResults.map((r, index) => {
       // error
-      if ('name' in r) {
+      if (isSendFailure(r)) {
         Logger.error(`Transaction ${startIndex + index} Error:`, r);
         return {
           receipt: null,
@@ -979,6 +1021,11 @@ export class AppService {
           signedTxs = await buildSendERC20Signatures_rs(configuration);
         }
         break;
+      case TxType.WORKLOAD:
+        {
+          signedTxs = await buildWorkloadSignatures_rs(configuration);
+        }
+        break;
     }
 
     if (config.pair_benchmark) {
@@ -1056,7 +1103,8 @@ export class AppService {
                 );
                 break;
               }
-              case TxType.NORMAL: {
+              case TxType.NORMAL:
+              case TxType.WORKLOAD: {
                 Logger.log(
                   `startNewRoundRevised Sending transactions | Account Index ${
                     this.currentAccountIndex - 1
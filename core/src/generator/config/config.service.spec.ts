@@ -0,0 +1,70 @@
+import { BenchmarkConfig } from '../../lib';
+import { ConfigService } from './config.service';
+
+// the shape of config-standard.json
+const baseConfig = {
+  network: {
+    evm_type: 'standard',
+    node_url: 'http://localhost:8545',
+    gas_limit: '100000',
+    gas_price: '5000000000000',
+    benchmark: true,
+  },
+  write_to_influxdb: false,
+  pair_benchmark: false,
+  account: {
+    mnemonic: 'test test test test test test test test test test test junk',
+    l1_holding: '10',
+    optimism_holding: '0.000001',
+    zksync_holding: '0.0005',
+  },
+  tx_type: 'normalTransfer',
+  rate_control: {
+    txs_per_batch: 40,
+    every_n_second: 2,
+    load_timeout: 120,
+    type: 'none',
+  },
+  total_tx: 400,
+};
+
+function validate(config: object): BenchmarkConfig {
+  return ConfigService['validateConfig'](config as unknown as BenchmarkConfig);
+}
+
+describe('ConfigService.validateConfig', () => {
+  it('accepts the base config', () => {
+    expect(() => validate(baseConfig)).not.toThrow();
+  });
+
+  it('accepts an account keyring', () => {
+    const keyring = { path: 'keyring.bin', password: 'secret' };
+    const validated = validate({
+      ...baseConfig,
+      account: { ...baseConfig.account, keyring },
+    });
+    expect(validated.account.keyring).toEqual(keyring);
+  });
+
+  it('rejects a keyring without password', () => {
+    const keyring = { path: 'keyring.bin' };
+    expect(() =>
+      validate({ ...baseConfig, account: { ...baseConfig.account, keyring } }),
+    ).toThrow('"account.keyring.password" is required');
+  });
+
+  it('accepts a workload for the workload tx type', () => {
+    const validated = validate({
+      ...baseConfig,
+      tx_type: 'workload',
+      workload: 'workload.json',
+    });
+    expect(validated.workload).toEqual('workload.json');
+  });
+
+  it('requires a workload for the workload tx type', () => {
+    expect(() => validate({ ...baseConfig, tx_type: 'workload' })).toThrow(
+      '"workload" is required',
+    );
+  });
+});
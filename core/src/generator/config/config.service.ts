@@ -55,10 +55,19 @@ export class ConfigService {
         l1_holding: Joi.string().required(),
         optimism_holding: Joi.string().required(),
         zksync_holding: Joi.string().required(),
+        keyring: Joi.object({
+          path: Joi.string().required(),
+          password: Joi.string().required(),
+        }).optional(),
       }),
       tx_type: Joi.string()
         .valid(...txTypeArray)
         .required(),
+      workload: Joi.string().when('tx_type', {
+        is: TxType.WORKLOAD,
+        then: Joi.required(),
+        otherwise: Joi.optional(),
+      }),
       rate_control: Joi.object({
         txs_per_batch: Joi.number().required(),
         every_n_second: Joi.number().required(),
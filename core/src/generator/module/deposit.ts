@@ -105,10 +105,10 @@ async function deposit(wallet: zkWallet, baseTokenAddress: string): Promise<(Err
       hash: receiptL1.transactionHash,
       blockNumber: receiptL1.blockNumber.toString(),
       success: true,
-      startTime: startTime.toString(),
-      sendTime: sendTime.toString(),
-      sendTimeCost: (endTime - startTime).toString(),
-      responseTimeCost: (endTimeL2 - endTime).toString(),
+      startTime: startTime,
+      sendTime: sendTime,
+      sendTimeCost: endTime - startTime,
+      responseTimeCost: endTimeL2 - endTime,
     } as Receipt;
   } catch (error) {
     return Error(`Deposit failed: ${error}`);
@@ -0,0 +1,27 @@
+import { Logger } from '@nestjs/common';
+import { rsBuildWorkloadSignatures } from 'rs-addon';
+import { BuildTxConfig } from '../utils/types';
+import { config } from '../config/config.service';
+
+export async function buildWorkloadSignatures_rs(
+  txConfig: BuildTxConfig,
+): Promise<string[]> {
+  if (!config.workload) {
+    throw new Error('`workload` is required for the workload tx type');
+  }
+
+  const result = await rsBuildWorkloadSignatures(
+    config.account.random_mnemonic,
+    txConfig.startIndex,
+    txConfig.endIndex,
+    txConfig.chainId,
+    0,
+    txConfig.gasPrice.toString(),
+    txConfig.gasLimit.toString(),
+    config.workload,
+  );
+
+  Logger.log(`All signed transactions built`);
+
+  return result;
+}
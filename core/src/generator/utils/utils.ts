@@ -1,4 +1,5 @@
 import { Logger } from '@nestjs/common';
+import { SendFailure } from 'rs-addon';
 
 export function waitForKeypress() {
   return new Promise<void>((resolve, reject) => {
@@ -31,3 +32,8 @@ export function waitForKeypress() {
     });
   });
 }
+
+// sends that failed come back from the addon as `SendFailure`s among the receipts
+export function isSendFailure(result: object): result is SendFailure {
+  return 'category' in result;
+}
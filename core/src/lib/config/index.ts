@@ -26,6 +26,8 @@ export interface BenchmarkConfig {
   account: Account;
   evm_type: EvmType;
   tx_type: TxType;
+  // path to the workload spec, for the `workload` tx type
+  workload?: string;
   rate_control: RateControl;
   total_tx: number;
 }
@@ -39,6 +41,13 @@ export interface Account {
   start_index: number;
   end_index: number;
   funding_factor: number;
+  // encrypted cache of derived keys, loaded before funding and saved after it
+  keyring?: Keyring;
+}
+
+export interface Keyring {
+  path: string;
+  password: string;
 }
 
 export enum EvmType {
@@ -54,6 +63,7 @@ export enum TxType {
   SWAP = 'swap',
   MINT_NFT = 'mint',
   DEPOSIT = 'deposit',
+  WORKLOAD = 'workload',
 }
 
 export enum RateControlType {
@@ -1,5 +1,8 @@
 extern crate napi_build;
 
 fn main() {
-  napi_build::setup();
+  // only the node addon links against node
+  if std::env::var_os("CARGO_FEATURE_NAPI").is_some() {
+    napi_build::setup();
+  }
 }
@@ -6,14 +6,218 @@
 export interface Receipt {
   hash: string
   blockNumber: string
+  blockTimestamp: string
+  transactionIndex: number
   success: boolean
-  startTime: string
-  sendTime: string
-  sendTimeCost: string
-  responseTimeCost: string
-}
-export function rsSendRawTransactions(httpProvider: string, transactions: Array<string>): Promise<Array<Error | Receipt | undefined | null>>
-export function rsFundErc20Tokens(mnemonic: string, randomMnemonic: string, httpProvider: string, contractAddress: string, tokenAddress: string, totalSubAccounts: number, perHolding: string): Promise<void>
-export function rsFundNativeTokens(mnemonic: string, randomMnemonic: string, httpProvider: string, contractAddress: string, totalSubAccounts: number, perHolding: string): Promise<void>
-export function rsBuildSignatures(mnemonic: string, startIndex: number, endIndex: number, chainId: number, data: string, nonce: number, value: number, toAddress: string, gasPrice: string, gasLimit: string): Promise<Array<string>>
-export function rsBuildNativeTokenTransferSignatures(mnemonic: string, toAddress: string, chainId: number, startIndex: number, endIndex: number, value: number, gasPrice: string, gasLimit: string): Promise<Array<string>>
+  from: string
+  nonce: number
+  txType: number
+  gasUsed: string
+  cumulativeGasUsed: string
+  effectiveGasPrice: string
+  startTime: number
+  sendTime: number
+  sendTimeCost: number
+  responseTimeCost: number
+}
+export interface RateControlOptions {
+  mode?: string
+  tps?: number
+  startTps?: number
+  maxInFlight?: number
+}
+export interface SendOptions {
+  connection?: ConnectionOptions
+  rateControl?: RateControlOptions
+  receiptTimeoutMs?: number
+  pollIntervalMs?: number
+  confirmations?: number
+}
+export interface ConnectionOptions {
+  poolMaxIdlePerHost?: number
+  poolIdleTimeoutMs?: number
+  connectTimeoutMs?: number
+  requestTimeoutMs?: number
+}
+export interface SendFailure {
+  category: string
+  rpcCode?: number
+  message: string
+  index: number
+}
+export function rsSendRawTransactions(httpProvider: string, transactions: Array<string>, options?: SendOptions | undefined | null): Promise<Array<Receipt | SendFailure>>
+export interface LatencyStats {
+  min: number
+  mean: number
+  p50: number
+  p90: number
+  p99: number
+  p999: number
+  max: number
+}
+export interface BlockStats {
+  blockNumber: number
+  blockTimestamp: number
+  txCount: number
+  gasUsed: number
+  tps?: number
+}
+export interface TimeBucket {
+  start: number
+  sent: number
+  confirmed: number
+}
+export interface FailureCount {
+  category: string
+  count: number
+}
+export interface RoundStats {
+  total: number
+  success: number
+  reverted: number
+  failed: number
+  successRatio: number
+  tps: number
+  sendLatency: LatencyStats
+  responseLatency: LatencyStats
+  blocks: Array<BlockStats>
+  buckets: Array<TimeBucket>
+  failures: Array<FailureCount>
+}
+export function rsSendRawTransactionsWithStats(httpProvider: string, transactions: Array<string>, options?: SendOptions | undefined | null, bucketMs?: number | undefined | null): Promise<RoundStats>
+export interface CoordinatorOptions {
+  listenAddress: string
+  workers: number
+  startIndex: number
+  endIndex: number
+  rounds: Array<number>
+  startDelayMs?: number
+  bucketMs?: number
+}
+export interface RoundSpec {
+  accounts: SignerSource
+  chainId: number
+  gasPrice: string
+  gasLimit: string
+  toAddress?: string
+  value?: number
+  workloadPath?: string
+  txOptions?: TxOptions
+}
+export interface WorkerOptions {
+  coordinatorAddress: string
+  httpProvider: string
+  transactions: RoundSpec
+  sendOptions?: SendOptions
+}
+export function rsRunCoordinator(options: CoordinatorOptions): Promise<Array<RoundStats>>
+export function rsRunWorker(options: WorkerOptions): Promise<number>
+export interface SignerSource {
+  mnemonic?: string
+  privateKeys?: Array<string>
+  keystores?: Array<string>
+  keystorePassword?: string
+  derivePath?: string
+  account?: number
+  indexOffset?: number
+}
+export interface AccountPartition {
+  workerId: number
+  workerCount: number
+  startIndex: number
+  endIndex: number
+  fundingStartIndex: number
+  fundingEndIndex: number
+}
+export function rsFundErc20Tokens(mnemonic: string | SignerSource, randomMnemonic: string | SignerSource, httpProvider: string, contractAddress: string, tokenAddress: string, totalSubAccounts: number, perHolding: string, partition?: AccountPartition | undefined | null): Promise<void>
+export function rsFundNativeTokens(mnemonic: string | SignerSource, randomMnemonic: string | SignerSource, httpProvider: string, contractAddress: string, totalSubAccounts: number, perHolding: string, partition?: AccountPartition | undefined | null): Promise<void>
+export function rsPartitionAccounts(workerId: number, workerCount: number, startIndex: number, endIndex: number): AccountPartition
+export interface CalldataArg {
+  kind: string
+  value?: string
+  min?: string
+  max?: string
+  start?: string
+  step?: string
+}
+export interface CalldataTemplate {
+  signature: string
+  args: Array<CalldataArg>
+  seed?: number
+}
+export interface AccessListEntry {
+  address: string
+  storageKeys: Array<string>
+}
+export interface TxOptions {
+  txType?: number
+  maxFeePerGas?: string
+  maxPriorityFeePerGas?: string
+  accessList?: Array<AccessListEntry>
+}
+export function rsBuildSignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, data: string, nonce: number, value: number, toAddress: string, gasPrice: string, gasLimit: string, txOptions?: TxOptions | undefined | null, calldata?: CalldataTemplate | undefined | null): Promise<Array<string>>
+export function rsBuildNativeTokenTransferSignatures(mnemonic: string | SignerSource, toAddress: string, chainId: number, startIndex: number, endIndex: number, value: number, gasPrice: string, gasLimit: string, txOptions?: TxOptions | undefined | null, nonce?: number | undefined | null): Promise<Array<string>>
+export function rsBuildSequentialSignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, data: string, value: number, toAddress: string, gasPrice: string, gasLimit: string, txsPerAccount: number, startNonces?: Array<number> | undefined | null, httpProvider?: string | undefined | null, txOptions?: TxOptions | undefined | null): Promise<Array<string>>
+export interface BlobOptions {
+  blobsPerTx?: number
+  fill?: string
+  maxFeePerGas?: string
+  maxPriorityFeePerGas?: string
+  maxFeePerBlobGas?: string
+}
+export function rsBuildBlobSignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, data: string, nonce: number, value: number, toAddress: string, gasPrice: string, gasLimit: string, blobOptions?: BlobOptions | undefined | null): Promise<Array<string>>
+export interface SetCodeOptions {
+  authorizationChainId?: number
+  authorizationNonce?: number
+  maxFeePerGas?: string
+  maxPriorityFeePerGas?: string
+}
+export interface AuthorizationTuple {
+  chainId: string
+  address: string
+  nonce: number
+  yParity: number
+  r: string
+  s: string
+}
+export function rsBuildAuthorizations(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, delegateAddress: string, nonce: number): Promise<Array<AuthorizationTuple>>
+export function rsBuildSetCodeSignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, data: string, nonce: number, value: number, toAddress: string, gasPrice: string, gasLimit: string, delegateAddress: string, setCodeOptions?: SetCodeOptions | undefined | null): Promise<Array<string>>
+export function rsBuildWorkloadSignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, nonce: number, gasPrice: string, gasLimit: string, workloadPath: string, txOptions?: TxOptions | undefined | null, seed?: number | undefined | null): Promise<Array<string>>
+export interface MixEntry {
+  kind: string
+  weight: number
+  toAddress: string
+  value?: number
+  data?: string
+  calldata?: CalldataTemplate
+  gasLimit?: string
+}
+export function rsBuildMixedSignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, gasPrice: string, gasLimit: string, entries: Array<MixEntry>, seed: number, txOptions?: TxOptions | undefined | null): Promise<Array<string>>
+export interface DeployOptions {
+  create2?: boolean
+  factoryAddress?: string
+  salt?: string
+}
+export interface DeploySignatures {
+  signedTxs: Array<string>
+  addresses: Array<string>
+}
+export function rsBuildDeploySignatures(mnemonic: string | SignerSource, startIndex: number, endIndex: number, chainId: number, bytecode: string, constructor: CalldataTemplate | undefined | null, nonce: number, value: number, gasPrice: string, gasLimit: string, deployOptions?: DeployOptions | undefined | null, txOptions?: TxOptions | undefined | null): Promise<DeploySignatures>
+export interface PresignOptions {
+  path: string
+  format?: string
+  transactions: RoundSpec
+  startIndex: number
+  rounds: Array<number>
+}
+export interface ReplayOptions {
+  format?: string
+  sendOptions?: SendOptions
+  roundIntervalMs?: number
+  bucketMs?: number
+}
+export function rsPresignTransactions(options: PresignOptions): Promise<number>
+export function rsReplayTransactions(httpProvider: string, path: string, options?: ReplayOptions | undefined | null): Promise<Array<RoundStats>>
+export function rsLoadKeyring(path: string, password: string): Promise<number>
+export function rsSaveKeyring(path: string, password: string): Promise<number>
+export function rsClearKeyring(): void
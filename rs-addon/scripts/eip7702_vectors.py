@@ -0,0 +1,183 @@
+#!/usr/bin/env python3
+"""Reference EIP-7702 vectors for the tests of src/sign/set_code.rs.
+
+Written from the specs (BIP-39, BIP-32, RFC 6979, EIP-2718, EIP-7702) with the Python standard
+library only, so the expected values don't come from the Rust code under test.
+
+    python3 scripts/eip7702_vectors.py
+"""
+
+import hashlib
+import hmac
+
+MNEMONIC = "test test test test test test test test test test test junk"
+PATH = "m/44'/60'/0'/0/1"
+CHAIN_ID = 31337
+DELEGATE = bytes.fromhex("5FbDB2315678afecb367f032d93F642f64180aa3")
+TO = bytes.fromhex("70997970C51812dc3A010C7d01b50e0d17dc79C8")
+
+# secp256k1
+P = 2**256 - 2**32 - 977
+N = 0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141
+G = (
+    0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798,
+    0x483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8,
+)
+
+
+def point_add(a, b):
+    if a is None:
+        return b
+    if b is None:
+        return a
+    if a[0] == b[0] and (a[1] + b[1]) % P == 0:
+        return None
+    if a == b:
+        slope = 3 * a[0] * a[0] * pow(2 * a[1], -1, P)
+    else:
+        slope = (b[1] - a[1]) * pow(b[0] - a[0], -1, P)
+    x = (slope * slope - a[0] - b[0]) % P
+    return (x, (slope * (a[0] - x) - a[1]) % P)
+
+
+def point_mul(k, point=G):
+    result = None
+    while k:
+        if k & 1:
+            result = point_add(result, point)
+        point = point_add(point, point)
+        k >>= 1
+    return result
+
+
+# keccak-256 as used by ethereum, the padding differs from sha3-256
+ROUND_CONSTANTS = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+]
+ROTATIONS = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+]
+MASK = 2**64 - 1
+
+
+def rotate(lane, bits):
+    return ((lane << bits) | (lane >> (64 - bits))) & MASK
+
+
+def keccak_f(state):
+    for rc in ROUND_CONSTANTS:
+        c = [state[x][0] ^ state[x][1] ^ state[x][2] ^ state[x][3] ^ state[x][4] for x in range(5)]
+        d = [c[(x - 1) % 5] ^ rotate(c[(x + 1) % 5], 1) for x in range(5)]
+        state = [[state[x][y] ^ d[x] for y in range(5)] for x in range(5)]
+        b = [[0] * 5 for _ in range(5)]
+        for x in range(5):
+            for y in range(5):
+                b[y][(2 * x + 3 * y) % 5] = rotate(state[x][y], ROTATIONS[x][y])
+        state = [
+            [b[x][y] ^ (~b[(x + 1) % 5][y] & b[(x + 2) % 5][y]) for y in range(5)]
+            for x in range(5)
+        ]
+        state[0][0] ^= rc
+    return state
+
+
+def keccak256(data):
+    rate = 136
+    padded = bytearray(data) + b"\x01" + b"\x00" * ((-len(data) - 1) % rate)
+    padded[-1] |= 0x80
+    state = [[0] * 5 for _ in range(5)]
+    for offset in range(0, len(padded), rate):
+        block = padded[offset:offset + rate]
+        for i in range(rate // 8):
+            state[i % 5][i // 5] ^= int.from_bytes(block[8 * i:8 * i + 8], "little")
+        state = keccak_f(state)
+    return b"".join(state[i % 5][i // 5].to_bytes(8, "little") for i in range(4))
+
+
+def rlp(item):
+    if isinstance(item, int):
+        item = item.to_bytes((item.bit_length() + 7) // 8, "big")
+    if isinstance(item, bytes):
+        if len(item) == 1 and item[0] < 0x80:
+            return item
+        return rlp_prefix(len(item), 0x80) + item
+    payload = b"".join(rlp(x) for x in item)
+    return rlp_prefix(len(payload), 0xC0) + payload
+
+
+def rlp_prefix(length, offset):
+    if length < 56:
+        return bytes([offset + length])
+    encoded = length.to_bytes((length.bit_length() + 7) // 8, "big")
+    return bytes([offset + 55 + len(encoded)]) + encoded
+
+
+def derive_key(mnemonic, path):
+    seed = hashlib.pbkdf2_hmac("sha512", mnemonic.encode(), b"mnemonic", 2048)
+    digest = hmac.new(b"Bitcoin seed", seed, hashlib.sha512).digest()
+    key, chain_code = int.from_bytes(digest[:32], "big"), digest[32:]
+    for part in path.split("/")[1:]:
+        index = int(part.rstrip("'")) + (2**31 if part.endswith("'") else 0)
+        if index >= 2**31:
+            data = b"\x00" + key.to_bytes(32, "big")
+        else:
+            x, y = point_mul(key)
+            data = bytes([2 + (y & 1)]) + x.to_bytes(32, "big")
+        digest = hmac.new(chain_code, data + index.to_bytes(4, "big"), hashlib.sha512).digest()
+        key, chain_code = (int.from_bytes(digest[:32], "big") + key) % N, digest[32:]
+    return key
+
+
+# deterministic k from rfc 6979 with hmac-sha256, s normalized to the lower half
+def sign(key, digest):
+    x = key.to_bytes(32, "big")
+    h = (int.from_bytes(digest, "big") % N).to_bytes(32, "big")
+    v, k = b"\x01" * 32, b"\x00" * 32
+    k = hmac.new(k, v + b"\x00" + x + h, hashlib.sha256).digest()
+    v = hmac.new(k, v, hashlib.sha256).digest()
+    k = hmac.new(k, v + b"\x01" + x + h, hashlib.sha256).digest()
+    v = hmac.new(k, v, hashlib.sha256).digest()
+    while True:
+        v = hmac.new(k, v, hashlib.sha256).digest()
+        nonce = int.from_bytes(v, "big")
+        if 1 <= nonce < N:
+            rx, ry = point_mul(nonce)
+            r = rx % N
+            s = pow(nonce, -1, N) * (int.from_bytes(digest, "big") + r * key) % N
+            if r and s:
+                y_parity = ry & 1
+                if s > N // 2:
+                    s, y_parity = N - s, y_parity ^ 1
+                return y_parity, r, s
+        k = hmac.new(k, v + b"\x00", hashlib.sha256).digest()
+        v = hmac.new(k, v, hashlib.sha256).digest()
+
+
+def main():
+    key = derive_key(MNEMONIC, PATH)
+
+    # authorization with nonce 1, the default of a set code tx with nonce 0
+    authorization = [CHAIN_ID, DELEGATE, 1]
+    y_parity, r, s = sign(key, keccak256(b"\x05" + rlp(authorization)))
+    print("authorization y_parity:", y_parity)
+    print("authorization r: 0x%064x" % r)
+    print("authorization s: 0x%064x" % s)
+
+    # chain id, nonce, max priority fee, max fee, gas, to, value, data, access list, authorizations
+    tx = [CHAIN_ID, 0, 0x3B9ACA00, 0x77359400, 0x186A0, TO, 0, bytes.fromhex("1234"), []]
+    tx.append([authorization + [y_parity, r, s]])
+    tx_parity, tx_r, tx_s = sign(key, keccak256(b"\x04" + rlp(tx)))
+    print("set code tx: 0x" + (b"\x04" + rlp(tx + [tx_parity, tx_r, tx_s])).hex())
+
+
+if __name__ == "__main__":
+    main()
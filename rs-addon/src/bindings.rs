@@ -0,0 +1,481 @@
+// node entry points of the addon, left out of builds without the `napi` feature such as the cli
+#![allow(non_snake_case)]
+
+use anyhow::Context;
+use napi::bindgen_prelude::Either;
+use napi::{Error, Result};
+
+use crate::cluster::coordinator::{run_coordinator, CoordinatorOptions};
+use crate::cluster::worker::{run_worker, WorkerOptions};
+use crate::fund::erc20::fund_erc20_tokens;
+use crate::fund::native::fund_native_tokens;
+use crate::logger::init_logger;
+use crate::replay::{replay_transactions, ReplayOptions};
+use crate::sign::account_range::AccountRange;
+use crate::sign::blob::{build_blob_signatures, BlobOptions};
+use crate::sign::calldata::CalldataTemplate;
+use crate::sign::deploy::{build_deploy_signatures, DeployOptions, DeploySignatures};
+use crate::sign::keyring::{keyring, load_keyring, save_keyring};
+use crate::sign::mix::{build_mixed_signatures, MixEntry};
+use crate::sign::partition::{partition_accounts, AccountPartition};
+use crate::sign::presign::{presign_transactions, PresignOptions};
+use crate::sign::set_code::{
+  build_authorizations, build_set_code_signatures, AuthorizationTuple, SetCodeOptions,
+};
+use crate::sign::signature::{
+  build_native_token_transfer_signatures, build_sequential_signatures, build_signatures, TxOptions,
+};
+use crate::sign::signer::SignerSource;
+use crate::sign::workload::build_workload_signatures;
+use crate::stats::{aggregate, RoundStats};
+use crate::transaction::{send_raw_transactions, Receipt, SendFailure, SendOptions};
+
+#[napi]
+pub async fn rsSendRawTransactions(
+  http_provider: String,
+  transactions: Vec<String>,
+  options: Option<SendOptions>,
+) -> Result<Vec<Either<Receipt, SendFailure>>> {
+  init_logger();
+  let results = send_raw_transactions(http_provider, transactions, options.unwrap_or_default())
+    .await
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+
+  Ok(
+    results
+      .into_iter()
+      .map(|result| match result {
+        Ok(receipt) => Either::A(receipt),
+        Err(e) => Either::B(SendFailure::from(e)),
+      })
+      .collect(),
+  )
+}
+
+#[napi]
+pub async fn rsSendRawTransactionsWithStats(
+  http_provider: String,
+  transactions: Vec<String>,
+  options: Option<SendOptions>,
+  bucket_ms: Option<u32>,
+) -> Result<RoundStats> {
+  init_logger();
+  let results = send_raw_transactions(http_provider, transactions, options.unwrap_or_default())
+    .await
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+
+  Ok(aggregate(&results, bucket_ms))
+}
+
+#[napi]
+pub async fn rsRunCoordinator(options: CoordinatorOptions) -> Result<Vec<RoundStats>> {
+  init_logger();
+  run_coordinator(options)
+    .await
+    .map_err(|e| Error::from_reason(format!("{:#}", e)))
+}
+
+#[napi]
+pub async fn rsRunWorker(options: WorkerOptions) -> Result<u32> {
+  init_logger();
+  run_worker(&options.coordinator_address.clone(), &options)
+    .await
+    .map_err(|e| Error::from_reason(format!("{:#}", e)))
+}
+
+#[napi]
+pub async fn rsFundERC20Tokens(
+  mnemonic: Either<String, SignerSource>,
+  random_mnemonic: Either<String, SignerSource>,
+  http_provider: String,
+  contract_address: String,
+  token_address: String,
+  total_sub_accounts: i64,
+  per_holding: String,
+  partition: Option<AccountPartition>,
+) {
+  init_logger();
+  fund_erc20_tokens(
+    mnemonic.into(),
+    random_mnemonic.into(),
+    http_provider,
+    contract_address,
+    token_address,
+    total_sub_accounts,
+    per_holding,
+    partition,
+  )
+  .await
+  .with_context(|| "Failed to fund erc20 tokens".to_string())
+  .unwrap();
+}
+
+#[napi]
+pub async fn rsFundNativeTokens(
+  mnemonic: Either<String, SignerSource>,
+  random_mnemonic: Either<String, SignerSource>,
+  http_provider: String,
+  contract_address: String,
+  total_sub_accounts: i64,
+  per_holding: String,
+  partition: Option<AccountPartition>,
+) {
+  init_logger();
+  fund_native_tokens(
+    mnemonic.into(),
+    random_mnemonic.into(),
+    http_provider,
+    contract_address,
+    total_sub_accounts,
+    per_holding,
+    partition,
+  )
+  .await
+  .with_context(|| "Failed to fund native tokens".to_string())
+  .unwrap();
+}
+
+#[napi]
+pub fn rsPartitionAccounts(
+  worker_id: u32,
+  worker_count: u32,
+  start_index: i64,
+  end_index: i64,
+) -> Result<AccountPartition> {
+  AccountRange::new(start_index, end_index)
+    .and_then(|accounts| partition_accounts(worker_id, worker_count, accounts))
+    .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+#[napi]
+pub async fn rsBuildSignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  nonce: i64,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  tx_options: Option<TxOptions>,
+  calldata: Option<CalldataTemplate>,
+) -> Vec<String> {
+  init_logger();
+  build_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    data,
+    nonce,
+    value,
+    to_address,
+    gas_price,
+    gas_limit,
+    tx_options,
+    calldata,
+  )
+  .await
+  .with_context(|| "Failed to build signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildNativeTokenTransferSignatures(
+  mnemonic: Either<String, SignerSource>,
+  to_address: String,
+  chain_id: i64,
+  start_index: i64,
+  end_index: i64,
+  value: i64,
+  gas_price: String,
+  gas_limit: String,
+  tx_options: Option<TxOptions>,
+  nonce: Option<i64>,
+) -> Vec<String> {
+  init_logger();
+  build_native_token_transfer_signatures(
+    mnemonic.into(),
+    to_address,
+    chain_id,
+    start_index,
+    end_index,
+    value,
+    gas_price,
+    gas_limit,
+    tx_options,
+    nonce,
+  )
+  .await
+  .with_context(|| "Failed to build native token transfer signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildSequentialSignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  txs_per_account: i64,
+  start_nonces: Option<Vec<i64>>,
+  http_provider: Option<String>,
+  tx_options: Option<TxOptions>,
+) -> Vec<String> {
+  init_logger();
+  build_sequential_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    data,
+    value,
+    to_address,
+    gas_price,
+    gas_limit,
+    txs_per_account,
+    start_nonces,
+    http_provider,
+    tx_options,
+  )
+  .await
+  .with_context(|| "Failed to build sequential signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildBlobSignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  nonce: i64,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  blob_options: Option<BlobOptions>,
+) -> Vec<String> {
+  init_logger();
+  build_blob_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    data,
+    nonce,
+    value,
+    to_address,
+    gas_price,
+    gas_limit,
+    blob_options,
+  )
+  .await
+  .with_context(|| "Failed to build blob signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildAuthorizations(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  delegate_address: String,
+  nonce: i64,
+) -> Vec<AuthorizationTuple> {
+  init_logger();
+  build_authorizations(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    delegate_address,
+    nonce,
+  )
+  .await
+  .with_context(|| "Failed to build authorizations".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildSetCodeSignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  nonce: i64,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  delegate_address: String,
+  set_code_options: Option<SetCodeOptions>,
+) -> Vec<String> {
+  init_logger();
+  build_set_code_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    data,
+    nonce,
+    value,
+    to_address,
+    gas_price,
+    gas_limit,
+    delegate_address,
+    set_code_options,
+  )
+  .await
+  .with_context(|| "Failed to build set code signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildWorkloadSignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  nonce: i64,
+  gas_price: String,
+  gas_limit: String,
+  workload_path: String,
+  tx_options: Option<TxOptions>,
+  seed: Option<i64>,
+) -> Vec<String> {
+  init_logger();
+  build_workload_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    nonce,
+    gas_price,
+    gas_limit,
+    workload_path,
+    tx_options,
+    seed,
+  )
+  .await
+  .with_context(|| "Failed to build workload signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildMixedSignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  gas_price: String,
+  gas_limit: String,
+  entries: Vec<MixEntry>,
+  seed: i64,
+  tx_options: Option<TxOptions>,
+) -> Vec<String> {
+  init_logger();
+  build_mixed_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    gas_price,
+    gas_limit,
+    entries,
+    seed,
+    tx_options,
+  )
+  .await
+  .with_context(|| "Failed to build mixed signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsBuildDeploySignatures(
+  mnemonic: Either<String, SignerSource>,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  bytecode: String,
+  constructor: Option<CalldataTemplate>,
+  nonce: i64,
+  value: i64,
+  gas_price: String,
+  gas_limit: String,
+  deploy_options: Option<DeployOptions>,
+  tx_options: Option<TxOptions>,
+) -> DeploySignatures {
+  init_logger();
+  build_deploy_signatures(
+    mnemonic.into(),
+    start_index,
+    end_index,
+    chain_id,
+    bytecode,
+    constructor,
+    nonce,
+    value,
+    gas_price,
+    gas_limit,
+    deploy_options,
+    tx_options,
+  )
+  .await
+  .with_context(|| "Failed to build deploy signatures".to_string())
+  .unwrap()
+}
+
+#[napi]
+pub async fn rsPresignTransactions(options: PresignOptions) -> Result<u32> {
+  init_logger();
+  presign_transactions(options)
+    .await
+    .map(|count| count as u32)
+    .map_err(|e| Error::from_reason(format!("{:#}", e)))
+}
+
+#[napi]
+pub async fn rsReplayTransactions(
+  http_provider: String,
+  path: String,
+  options: Option<ReplayOptions>,
+) -> Result<Vec<RoundStats>> {
+  init_logger();
+  replay_transactions(http_provider, path, options.unwrap_or_default())
+    .await
+    .map_err(|e| Error::from_reason(format!("{:#}", e)))
+}
+
+#[napi]
+pub async fn rsLoadKeyring(path: String, password: String) -> Result<u32> {
+  init_logger();
+  load_keyring(path, password)
+    .await
+    .map(|count| count as u32)
+    .map_err(|e| Error::from_reason(format!("Failed to load keyring: {:#}", e)))
+}
+
+#[napi]
+pub async fn rsSaveKeyring(path: String, password: String) -> Result<u32> {
+  init_logger();
+  save_keyring(path, password)
+    .await
+    .map(|count| count as u32)
+    .map_err(|e| Error::from_reason(format!("Failed to save keyring: {:#}", e)))
+}
+
+#[napi]
+pub fn rsClearKeyring() {
+  keyring().clear();
+}
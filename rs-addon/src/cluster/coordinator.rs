@@ -0,0 +1,432 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use tokio::net::TcpListener;
+
+use crate::cluster::protocol::{
+  unix_millis, Connection, CoordinatorMessage, WorkerMessage, PROTOCOL_VERSION,
+};
+use crate::sign::account_range::AccountRange;
+use crate::sign::partition::{partition_accounts, AccountPartition};
+use crate::stats::{aggregate, RoundStats};
+use crate::transaction::{Receipt, SendError, SendFailure};
+
+const DEFAULT_START_DELAY_MS: u32 = 3000;
+// a connection that stays silent that long isn't a worker
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone)]
+pub struct CoordinatorOptions {
+  // e.g. `0.0.0.0:7070`
+  pub listen_address: String,
+  pub workers: u32,
+  // benchmark accounts split among the workers, inclusive
+  pub start_index: i64,
+  pub end_index: i64,
+  // transactions every worker sends per round
+  pub rounds: Vec<u32>,
+  // time between announcing a round and its start, leaves room for the message to reach every
+  // worker
+  pub start_delay_ms: Option<u32>,
+  pub bucket_ms: Option<u32>,
+}
+
+struct Worker {
+  id: u32,
+  address: SocketAddr,
+  connection: Connection,
+}
+
+impl Worker {
+  async fn recv(&mut self) -> Result<WorkerMessage> {
+    let message = self
+      .connection
+      .recv()
+      .await
+      .with_context(|| format!("Lost worker {} ({})", self.id, self.address))?;
+    if let WorkerMessage::Failed { message } = message {
+      bail!("worker {} ({}) failed: {}", self.id, self.address, message);
+    }
+    Ok(message)
+  }
+}
+
+pub struct Coordinator {
+  listener: TcpListener,
+  options: CoordinatorOptions,
+  partitions: Vec<AccountPartition>,
+}
+
+impl Coordinator {
+  // checks the plan before any worker connects, so a bad one fails right away
+  pub async fn bind(options: CoordinatorOptions) -> Result<Self> {
+    if options.rounds.is_empty() || options.rounds.contains(&0) {
+      bail!("every round should send at least 1 transaction");
+    }
+    let accounts = AccountRange::new(options.start_index, options.end_index)?;
+    let partitions = (0..options.workers)
+      .map(|worker| partition_accounts(worker, options.workers, accounts))
+      .collect::<Result<Vec<_>>>()?;
+
+    let needed: u64 = options.rounds.iter().map(|&txs| txs as u64).sum();
+    // the last workers get the smallest shares
+    let smallest = partitions
+      .last()
+      .map_or(0, |p| (p.end_index - p.start_index + 1) as u64);
+    if needed > smallest {
+      bail!(
+        "every worker needs {} accounts for its rounds, but some only have {}",
+        needed,
+        smallest
+      );
+    }
+
+    let listener = TcpListener::bind(&options.listen_address)
+      .await
+      .with_context(|| format!("Failed to listen on {}", options.listen_address))?;
+
+    Ok(Coordinator {
+      listener,
+      options,
+      partitions,
+    })
+  }
+
+  pub fn local_addr(&self) -> Result<SocketAddr> {
+    Ok(self.listener.local_addr()?)
+  }
+
+  // waits for every worker, then runs the rounds in lockstep: a round starts on all workers at the
+  // same time once each of them finished the previous one. Returns the stats of every round over
+  // the results of all workers
+  pub async fn run(self) -> Result<Vec<RoundStats>> {
+    log::info!(
+      "waiting for {} workers on {}",
+      self.partitions.len(),
+      self.local_addr()?
+    );
+    let mut workers = Vec::with_capacity(self.partitions.len());
+    for partition in self.partitions.iter() {
+      // anything else reaching the port, e.g. a port scanner or a stale worker, is dropped
+      let (address, connection) = loop {
+        let (stream, address) = self.listener.accept().await?;
+        let mut connection = Connection::new(stream);
+        match hello(&mut connection).await {
+          Ok(()) => break (address, connection),
+          Err(e) => log::warn!("dropped connection from {}: {:#}", address, e),
+        }
+      };
+      let mut worker = Worker {
+        id: partition.worker_id,
+        address,
+        connection,
+      };
+      worker
+        .connection
+        .send(&CoordinatorMessage::Assign {
+          partition: partition.clone(),
+          rounds: self.options.rounds.clone(),
+        })
+        .await?;
+      log::info!(
+        "worker {} ({}) joined, accounts {} - {}",
+        worker.id,
+        address,
+        partition.start_index,
+        partition.end_index
+      );
+      workers.push(worker);
+    }
+
+    for worker in workers.iter_mut() {
+      match worker.recv().await? {
+        WorkerMessage::Ready => {}
+        message => bail!("expected worker {} ready, got {:?}", worker.id, message),
+      }
+    }
+    log::info!("all {} workers ready", workers.len());
+
+    let start_delay = self
+      .options
+      .start_delay_ms
+      .unwrap_or(DEFAULT_START_DELAY_MS) as f64;
+    let mut stats = Vec::with_capacity(self.options.rounds.len());
+    for round in 0..self.options.rounds.len() as u32 {
+      let start_at = unix_millis() + start_delay;
+      for worker in workers.iter_mut() {
+        worker
+          .connection
+          .send(&CoordinatorMessage::Start { round, start_at })
+          .await?;
+      }
+
+      // results of the workers one after the other, in worker id order
+      let mut results = vec![];
+      for worker in workers.iter_mut() {
+        let offset = results.len();
+        loop {
+          match worker.recv().await? {
+            WorkerMessage::Results {
+              round: result_round,
+              results: chunk,
+            } if result_round == round => {
+              results.extend(offset_failures(chunk, offset));
+            }
+            WorkerMessage::RoundDone { round: done_round } if done_round == round => break,
+            message => bail!(
+              "unexpected message from worker {} in round {}: {:?}",
+              worker.id,
+              round,
+              message
+            ),
+          }
+        }
+      }
+
+      let round_stats = aggregate(&results, self.options.bucket_ms);
+      log::info!(
+        "round {} | {} transactions | {} success | {:.2} tps",
+        round,
+        round_stats.total,
+        round_stats.success,
+        round_stats.tps
+      );
+      stats.push(round_stats);
+    }
+
+    for worker in workers.iter_mut() {
+      worker.connection.send(&CoordinatorMessage::Finish).await?;
+    }
+    // let the workers read `Finish` before the connections drop
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    Ok(stats)
+  }
+}
+
+// waits for the hello opening every worker connection
+async fn hello(connection: &mut Connection) -> Result<()> {
+  let message = tokio::time::timeout(HELLO_TIMEOUT, connection.recv())
+    .await
+    .with_context(|| format!("no hello after {} ms", HELLO_TIMEOUT.as_millis()))??;
+  match message {
+    WorkerMessage::Hello { version } if version == PROTOCOL_VERSION => Ok(()),
+    WorkerMessage::Hello { version } => bail!(
+      "protocol version {}, expected {}",
+      version,
+      PROTOCOL_VERSION
+    ),
+    message => bail!("expected hello, got {:?}", message),
+  }
+}
+
+// failures index the transactions a worker sent, shifted by `offset` they index the results of the
+// whole round instead
+fn offset_failures(
+  results: Vec<std::result::Result<Receipt, SendFailure>>,
+  offset: usize,
+) -> impl Iterator<Item = std::result::Result<Receipt, SendError>> {
+  results.into_iter().map(move |result| {
+    result.map_err(|failure| {
+      let mut error = SendError::from(failure);
+      error.index += offset;
+      error
+    })
+  })
+}
+
+pub async fn run_coordinator(options: CoordinatorOptions) -> Result<Vec<RoundStats>> {
+  Coordinator::bind(options).await?.run().await
+}
+
+#[cfg(test)]
+mod tests {
+  use async_trait::async_trait;
+  use tokio::io::AsyncWriteExt;
+  use tokio::net::TcpStream;
+  use tokio::sync::Mutex;
+
+  use crate::cluster::coordinator::{offset_failures, Coordinator, CoordinatorOptions};
+  use crate::cluster::protocol::unix_millis;
+  use crate::cluster::worker::{run_worker, WorkerRounds};
+  use crate::sign::account_range::AccountRange;
+  use crate::transaction::{Receipt, SendError, SendErrorCategory, SendFailure};
+
+  // stands in for signing and a node: a transaction is its account index, accounts divisible by 5
+  // fail. Remembers the accounts and time of every send
+  #[derive(Default)]
+  struct FakeRounds {
+    sent: Mutex<Vec<(Vec<u32>, f64)>>,
+  }
+
+  #[async_trait]
+  impl WorkerRounds for FakeRounds {
+    async fn build(&self, accounts: AccountRange) -> anyhow::Result<Vec<String>> {
+      Ok(accounts.indexes().map(|index| index.to_string()).collect())
+    }
+
+    async fn send(
+      &self,
+      transactions: Vec<String>,
+    ) -> anyhow::Result<Vec<Result<Receipt, SendError>>> {
+      let now = unix_millis();
+      let accounts: Vec<u32> = transactions.iter().map(|tx| tx.parse().unwrap()).collect();
+      let results = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, &account)| {
+          if account % 5 == 0 {
+            return Err(SendError::new(
+              SendErrorCategory::NonceTooLow,
+              i,
+              "nonce too low",
+            ));
+          }
+          Ok(Receipt {
+            hash: format!("0x{:064x}", account),
+            block_number: "1".to_string(),
+            block_timestamp: "1".to_string(),
+            transaction_index: i as u32,
+            success: true,
+            from: String::new(),
+            nonce: 0,
+            tx_type: 0,
+            gas_used: "21000".to_string(),
+            cumulative_gas_used: "21000".to_string(),
+            effective_gas_price: "1".to_string(),
+            start_time: now,
+            send_time: now,
+            send_time_cost: 1.0,
+            response_time_cost: 2.0,
+          })
+        })
+        .collect();
+      self.sent.lock().await.push((accounts, now));
+      Ok(results)
+    }
+  }
+
+  #[tokio::test]
+  async fn test_coordinator_rounds() {
+    let coordinator = Coordinator::bind(CoordinatorOptions {
+      listen_address: "127.0.0.1:0".to_string(),
+      workers: 3,
+      start_index: 1,
+      end_index: 30,
+      rounds: vec![4, 6],
+      start_delay_ms: Some(200),
+      bucket_ms: None,
+    })
+    .await
+    .unwrap();
+    let address = coordinator.local_addr().unwrap().to_string();
+    let coordinator = tokio::spawn(coordinator.run());
+
+    // connections that aren't workers don't take a worker's place
+    for line in ["GET / HTTP/1.1", r#"{"type":"hello","version":0}"#] {
+      let mut stream = TcpStream::connect(&address).await.unwrap();
+      stream
+        .write_all(format!("{}\n", line).as_bytes())
+        .await
+        .unwrap();
+    }
+
+    let workers: Vec<_> = (0..3)
+      .map(|_| {
+        let address = address.clone();
+        tokio::spawn(async move {
+          let rounds = FakeRounds::default();
+          let count = run_worker(&address, &rounds).await.unwrap();
+          (count, rounds.sent.into_inner())
+        })
+      })
+      .collect();
+
+    let stats = coordinator.await.unwrap().unwrap();
+    let mut sent = vec![];
+    for worker in workers {
+      let (count, worker_sent) = worker.await.unwrap();
+      assert_eq!(count, 2);
+      sent.push(worker_sent);
+    }
+
+    // workers own accounts 1 - 10, 11 - 20 and 21 - 30 and go through them in order, connecting
+    // in any order
+    sent.sort_by_key(|worker_sent| worker_sent[0].0[0]);
+    for (worker, worker_sent) in sent.iter().enumerate() {
+      let first = worker as u32 * 10 + 1;
+      assert_eq!(worker_sent[0].0, (first..first + 4).collect::<Vec<_>>());
+      assert_eq!(
+        worker_sent[1].0,
+        (first + 4..first + 10).collect::<Vec<_>>()
+      );
+    }
+
+    // every worker starts a round at the same time
+    for round in 0..2 {
+      let starts: Vec<f64> = sent
+        .iter()
+        .map(|worker_sent| worker_sent[round].1)
+        .collect();
+      let spread = starts.iter().cloned().fold(f64::MIN, f64::max)
+        - starts.iter().cloned().fold(f64::MAX, f64::min);
+      assert!(spread < 100.0, "round {} starts {} ms apart", round, spread);
+    }
+
+    assert_eq!(stats.len(), 2);
+    // round 0 sends accounts 1 - 4, 11 - 14 and 21 - 24, none divisible by 5
+    assert_eq!(
+      (stats[0].total, stats[0].success, stats[0].failed),
+      (12, 12, 0)
+    );
+    // round 1 sends 5 - 10, 15 - 20 and 25 - 30, two failures each
+    assert_eq!(
+      (stats[1].total, stats[1].success, stats[1].failed),
+      (18, 12, 6)
+    );
+    assert_eq!(stats[1].failures[0].category, "nonce_too_low");
+  }
+
+  #[test]
+  fn test_offset_failures() {
+    let failure = |index: u32| SendFailure {
+      category: "nonce_too_low".to_string(),
+      rpc_code: Some(-32000),
+      message: "nonce too low".to_string(),
+      index,
+    };
+    // the second worker's failures of its transactions 0 and 3, after 4 results of the first
+    let indexes: Vec<usize> = offset_failures(vec![Err(failure(0)), Err(failure(3))], 4)
+      .map(|result| result.unwrap_err().index)
+      .collect();
+    assert_eq!(indexes, vec![4, 7]);
+  }
+
+  #[tokio::test]
+  async fn test_coordinator_plan() {
+    let options = CoordinatorOptions {
+      listen_address: "127.0.0.1:0".to_string(),
+      workers: 3,
+      start_index: 1,
+      end_index: 30,
+      rounds: vec![6, 5],
+      start_delay_ms: None,
+      bucket_ms: None,
+    };
+    assert!(Coordinator::bind(options.clone()).await.is_err());
+    assert!(Coordinator::bind(CoordinatorOptions {
+      rounds: vec![],
+      ..options.clone()
+    })
+    .await
+    .is_err());
+    assert!(Coordinator::bind(CoordinatorOptions {
+      workers: 0,
+      ..options
+    })
+    .await
+    .is_err());
+  }
+}
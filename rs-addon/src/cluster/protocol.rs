@@ -0,0 +1,135 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+
+use crate::sign::partition::AccountPartition;
+use crate::transaction::{Receipt, SendFailure};
+
+// bumped on any incompatible message change, workers of another version are refused
+pub const PROTOCOL_VERSION: u32 = 1;
+// results per `Results` message, keeps every line of a large round reasonably small
+pub const RESULTS_CHUNK: usize = 1000;
+
+// messages are single lines of json, tagged with their `type`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerMessage {
+  Hello {
+    version: u32,
+  },
+  // every round is signed, waiting for the first start
+  Ready,
+  Results {
+    round: u32,
+    results: Vec<std::result::Result<Receipt, SendFailure>>,
+  },
+  RoundDone {
+    round: u32,
+  },
+  Failed {
+    message: String,
+  },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoordinatorMessage {
+  // the worker's accounts and the transactions it sends per round, taken from its accounts in order
+  Assign {
+    partition: AccountPartition,
+    rounds: Vec<u32>,
+  },
+  // `start_at` is a unix timestamp in milliseconds, machines are expected to keep their clocks in
+  // sync, e.g. with ntp
+  Start {
+    round: u32,
+    start_at: f64,
+  },
+  Finish,
+}
+
+pub fn unix_millis() -> f64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_micros() as f64
+    / 1000.0
+}
+
+pub struct Connection {
+  lines: Lines<BufReader<OwnedReadHalf>>,
+  writer: OwnedWriteHalf,
+}
+
+impl Connection {
+  pub fn new(stream: TcpStream) -> Self {
+    // messages are small and latency matters more than throughput for `Start`
+    _ = stream.set_nodelay(true);
+    let (reader, writer) = stream.into_split();
+    Connection {
+      lines: BufReader::new(reader).lines(),
+      writer,
+    }
+  }
+
+  pub async fn connect(address: impl ToSocketAddrs) -> Result<Self> {
+    let stream = TcpStream::connect(address)
+      .await
+      .with_context(|| "Failed to connect to coordinator".to_string())?;
+    Ok(Self::new(stream))
+  }
+
+  pub async fn send<T: Serialize>(&mut self, message: &T) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    self
+      .writer
+      .write_all(&line)
+      .await
+      .with_context(|| "Failed to send message".to_string())
+  }
+
+  pub async fn recv<T: DeserializeOwned>(&mut self) -> Result<T> {
+    let Some(line) = self.lines.next_line().await? else {
+      bail!("connection closed");
+    };
+    serde_json::from_str(&line).with_context(|| format!("Invalid message: {}", line))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::cluster::protocol::{CoordinatorMessage, WorkerMessage};
+  use crate::transaction::SendFailure;
+
+  #[test]
+  fn test_message_format() {
+    let start = CoordinatorMessage::Start {
+      round: 2,
+      start_at: 1_700_000_000_000.5,
+    };
+    assert_eq!(
+      serde_json::to_string(&start).unwrap(),
+      r#"{"type":"start","round":2,"start_at":1700000000000.5}"#
+    );
+
+    let results: WorkerMessage = serde_json::from_str(
+      r#"{"type":"results","round":0,"results":[{"Err":{"category":"nonce_too_low","rpc_code":-32000,"message":"nonce too low","index":3}}]}"#,
+    )
+    .unwrap();
+    let WorkerMessage::Results { round, results } = results else {
+      panic!("expected results");
+    };
+    assert_eq!(round, 0);
+    let failure: SendFailure = results.into_iter().next().unwrap().err().unwrap();
+    assert_eq!(
+      (failure.category.as_str(), failure.rpc_code, failure.index),
+      ("nonce_too_low", Some(-32000), 3)
+    );
+  }
+}
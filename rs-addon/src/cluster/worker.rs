@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+use crate::cluster::protocol::{
+  unix_millis, Connection, CoordinatorMessage, WorkerMessage, PROTOCOL_VERSION, RESULTS_CHUNK,
+};
+use crate::sign::account_range::AccountRange;
+use crate::sign::partition::AccountPartition;
+use crate::sign::round::RoundSpec;
+use crate::transaction::{send_raw_transactions, Receipt, SendError, SendFailure, SendOptions};
+
+// what a worker does with its accounts: every round is built up front, then sent on the
+// coordinator's signal
+#[async_trait]
+pub trait WorkerRounds: Send + Sync {
+  async fn build(&self, accounts: AccountRange) -> Result<Vec<String>>;
+
+  async fn send(&self, transactions: Vec<String>) -> Result<Vec<Result<Receipt, SendError>>>;
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone)]
+pub struct WorkerOptions {
+  pub coordinator_address: String,
+  pub http_provider: String,
+  // the coordinator picks which of the accounts this worker signs with
+  pub transactions: RoundSpec,
+  pub send_options: Option<SendOptions>,
+}
+
+#[async_trait]
+impl WorkerRounds for WorkerOptions {
+  async fn build(&self, accounts: AccountRange) -> Result<Vec<String>> {
+    self.transactions.build(accounts).await
+  }
+
+  async fn send(&self, transactions: Vec<String>) -> Result<Vec<Result<Receipt, SendError>>> {
+    send_raw_transactions(
+      self.http_provider.clone(),
+      transactions,
+      self.send_options.clone().unwrap_or_default(),
+    )
+    .await
+  }
+}
+
+async fn build_rounds(
+  rounds: &impl WorkerRounds,
+  partition: &AccountPartition,
+  sizes: &[u32],
+) -> Result<Vec<Vec<String>>> {
+  let accounts = partition.accounts()?;
+  let mut built = Vec::with_capacity(sizes.len());
+  let mut start = *accounts.indexes().start() as i64;
+  for &size in sizes {
+    let range = AccountRange::with_len(start, size as i64)?;
+    if range.indexes().end() > accounts.indexes().end() {
+      bail!(
+        "rounds need more accounts than the {} assigned",
+        accounts.len()
+      );
+    }
+    built.push(rounds.build(range).await?);
+    start += size as i64;
+  }
+  Ok(built)
+}
+
+// tells the coordinator why the worker stops, its error beats a dropped connection. Best effort
+async fn report_failure(connection: &mut Connection, error: anyhow::Error) -> anyhow::Error {
+  _ = connection
+    .send(&WorkerMessage::Failed {
+      message: format!("{:#}", error),
+    })
+    .await;
+  error
+}
+
+// joins the coordinator at `address` and runs the rounds it starts until it's done, returns the
+// number of rounds run
+pub async fn run_worker(address: &str, rounds: &impl WorkerRounds) -> Result<u32> {
+  let mut connection = Connection::connect(address).await?;
+  connection
+    .send(&WorkerMessage::Hello {
+      version: PROTOCOL_VERSION,
+    })
+    .await?;
+
+  let (partition, sizes) = match connection.recv().await? {
+    CoordinatorMessage::Assign { partition, rounds } => (partition, rounds),
+    message => bail!("expected an assignment, got {:?}", message),
+  };
+  log::info!(
+    "worker {} of {}, accounts {} - {}, {} rounds",
+    partition.worker_id,
+    partition.worker_count,
+    partition.start_index,
+    partition.end_index,
+    sizes.len()
+  );
+
+  // signing happens before the first start, so it doesn't skew the measured rounds
+  let mut built = match build_rounds(rounds, &partition, &sizes).await {
+    Ok(built) => built,
+    Err(e) => return Err(report_failure(&mut connection, e).await),
+  };
+  connection.send(&WorkerMessage::Ready).await?;
+
+  let mut count = 0;
+  loop {
+    let (round, start_at) = match connection.recv().await? {
+      CoordinatorMessage::Start { round, start_at } => (round, start_at),
+      CoordinatorMessage::Finish => break,
+      message => bail!("expected a round start, got {:?}", message),
+    };
+    let Some(transactions) = built.get_mut(round as usize).map(std::mem::take) else {
+      bail!("round {} was not assigned", round);
+    };
+
+    let wait = start_at - unix_millis();
+    if wait > 0.0 {
+      tokio::time::sleep(Duration::from_secs_f64(wait / 1000.0)).await;
+    } else {
+      log::warn!("round {} started {:.0} ms late", round, -wait);
+    }
+
+    let results = match rounds.send(transactions).await {
+      Ok(results) => results,
+      Err(e) => return Err(report_failure(&mut connection, e).await),
+    };
+    log::info!("round {} sent {} transactions", round, results.len());
+
+    let mut results = results
+      .into_iter()
+      .map(|result| result.map_err(SendFailure::from))
+      .peekable();
+    while results.peek().is_some() {
+      let chunk = results.by_ref().take(RESULTS_CHUNK).collect();
+      connection
+        .send(&WorkerMessage::Results {
+          round,
+          results: chunk,
+        })
+        .await?;
+    }
+    connection.send(&WorkerMessage::RoundDone { round }).await?;
+    count += 1;
+  }
+
+  Ok(count)
+}
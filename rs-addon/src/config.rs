@@ -0,0 +1,178 @@
+use anyhow::{bail, Context, Result};
+use ethers::types::U256;
+use ethers::utils::parse_ether;
+use serde::Deserialize;
+
+// the `config-*.json` read by the Nest app, only the fields the Rust engine uses. Unknown fields
+// such as `write_to_influxdb` are ignored
+#[derive(Deserialize)]
+pub struct BenchmarkConfig {
+  pub network: BaseNetwork,
+  pub account: AccountConfig,
+  pub tx_type: String,
+  // path to the workload spec, for the `workload` tx type
+  pub workload: Option<String>,
+  pub rate_control: RateControlConfig,
+  pub total_tx: u32,
+}
+
+#[derive(Deserialize)]
+pub struct NetworkConfig {
+  pub evm_type: String,
+  pub node_url: String,
+  // decimal or 0x prefixed hex, like the BigNumber strings of the app
+  pub gas_limit: String,
+  pub gas_price: String,
+  #[serde(default)]
+  pub benchmark: bool,
+}
+
+#[derive(Deserialize)]
+pub struct BaseNetwork {
+  #[serde(flatten)]
+  pub network: NetworkConfig,
+  pub layer2: Option<NetworkConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct AccountConfig {
+  pub mnemonic: String,
+  // ether per benchmark account, by evm type
+  pub l1_holding: String,
+  pub optimism_holding: String,
+  pub zksync_holding: String,
+  // the app generates a new one per run, separate cli runs have to share it
+  pub random_mnemonic: Option<String>,
+  pub keyring: Option<KeyringConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct KeyringConfig {
+  pub path: String,
+  pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct RateControlConfig {
+  pub txs_per_batch: u32,
+  pub every_n_second: u32,
+}
+
+impl BenchmarkConfig {
+  pub fn load(path: &str) -> Result<Self> {
+    let content =
+      std::fs::read_to_string(path).with_context(|| format!("Failed to read config: {}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse config: {}", path))
+  }
+
+  // the network flagged with `benchmark`, the base network first
+  pub fn benchmark_network(&self) -> Result<&NetworkConfig> {
+    if self.network.network.benchmark {
+      return Ok(&self.network.network);
+    }
+    match &self.network.layer2 {
+      Some(layer2) if layer2.benchmark => Ok(layer2),
+      _ => bail!("no network is flagged with `benchmark`"),
+    }
+  }
+
+  // wei funded to every benchmark account
+  pub fn per_holding(&self) -> Result<U256> {
+    let network = self.benchmark_network()?;
+    let ether = match network.evm_type.as_str() {
+      "optimism" => &self.account.optimism_holding,
+      "zkSync" => &self.account.zksync_holding,
+      // just enough for a single transaction
+      "hermez" => return Ok(network.gas_price()? * network.gas_limit()?),
+      _ => &self.account.l1_holding,
+    };
+    parse_ether(ether).with_context(|| format!("Invalid holding: {}", ether))
+  }
+}
+
+impl NetworkConfig {
+  pub fn gas_price(&self) -> Result<U256> {
+    parse_quantity(&self.gas_price)
+      .with_context(|| format!("Invalid gas price: {}", self.gas_price))
+  }
+
+  pub fn gas_limit(&self) -> Result<U256> {
+    parse_quantity(&self.gas_limit)
+      .with_context(|| format!("Invalid gas limit: {}", self.gas_limit))
+  }
+}
+
+fn parse_quantity(value: &str) -> Result<U256> {
+  Ok(match value.strip_prefix("0x") {
+    Some(hex) => U256::from_str_radix(hex, 16)?,
+    None => U256::from_dec_str(value)?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::types::U256;
+
+  use crate::config::BenchmarkConfig;
+
+  #[test]
+  fn test_benchmark_config() {
+    let mut config: BenchmarkConfig = serde_json::from_str(
+      r#"{
+        "network": {
+          "evm_type": "standard",
+          "node_url": "http://localhost:8545",
+          "gas_limit": "100000",
+          "gas_price": "5000000000000",
+          "benchmark": true,
+          "layer2": {
+            "node_url": "http://localhost:3050",
+            "gas_limit": "10000000",
+            "gas_price": "0x10000000",
+            "evm_type": "hermez"
+          }
+        },
+        "write_to_influxdb": false,
+        "pair_benchmark": false,
+        "account": {
+          "mnemonic": "test test test test test test test test test test test junk",
+          "l1_holding": "10",
+          "optimism_holding": "0.000001",
+          "zksync_holding": "0.0005"
+        },
+        "tx_type": "normalTransfer",
+        "rate_control": {
+          "txs_per_batch": 40,
+          "every_n_second": 2,
+          "load_timeout": 120,
+          "type": "none"
+        },
+        "total_tx": 400
+      }"#,
+    )
+    .unwrap();
+
+    let network = config.benchmark_network().unwrap();
+    assert_eq!(network.node_url, "http://localhost:8545");
+    assert_eq!(
+      network.gas_price().unwrap(),
+      U256::from(5_000_000_000_000u64)
+    );
+    assert_eq!(
+      config.per_holding().unwrap(),
+      U256::exp10(19) // 10 ether
+    );
+
+    config.network.network.benchmark = false;
+    let layer2 = config.network.layer2.as_mut().unwrap();
+    assert_eq!(layer2.gas_price().unwrap(), U256::from(0x10000000));
+    layer2.benchmark = true;
+    assert_eq!(
+      config.per_holding().unwrap(),
+      U256::from(0x10000000u64 * 10_000_000)
+    );
+
+    config.network.layer2.as_mut().unwrap().benchmark = false;
+    assert!(config.benchmark_network().is_err());
+  }
+}
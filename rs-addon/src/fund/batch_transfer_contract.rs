@@ -3,6 +3,8 @@ use std::sync::Arc;
 use anyhow::{Context, Result};
 use ethers::prelude::*;
 
+use crate::provider::{connect_provider, ConnectionOptions};
+
 abigen!(
   BatchTransferV1,
   r#"[
@@ -34,7 +36,8 @@ pub async fn estimate_gas_native(
   contributors: Vec<Address>,
   amounts: Vec<U256>,
 ) -> Result<EstimateGas> {
-  let provider = Provider::<Http>::try_from(http_provider)
+  let provider = connect_provider(&http_provider, &ConnectionOptions::default())
+    .await
     .with_context(|| "Failed to create provider".to_string())?;
 
   let client = SignerMiddleware::new(provider.clone(), signer);
@@ -71,7 +74,8 @@ pub async fn estimate_gas_erc20(
   contributors: Vec<Address>,
   amounts: Vec<U256>,
 ) -> Result<EstimateGas> {
-  let provider = Provider::<Http>::try_from(http_provider)
+  let provider = connect_provider(&http_provider, &ConnectionOptions::default())
+    .await
     .with_context(|| "Failed to create provider".to_string())?;
 
   let client = SignerMiddleware::new(provider.clone(), signer);
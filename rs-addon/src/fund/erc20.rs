@@ -1,7 +1,10 @@
 use crate::fund::batch_transfer_contract::{
   estimate_gas_erc20, BatchTransferV1Calls, MultisendTokenCall, BATCH_SIZE, ERC20,
 };
-use crate::sign::signer::{get_child_signer, get_child_signers, get_child_signers_with_range};
+use crate::provider::{connect_provider, ConnectionOptions};
+use crate::sign::account_range::AccountRange;
+use crate::sign::partition::{single_worker, AccountPartition};
+use crate::sign::signer::SignerSource;
 use std::process::exit;
 use std::sync::Arc;
 
@@ -29,7 +32,8 @@ async fn fund_tokens(
     panic!("[ERC20] to_addresses length should be less than 200")
   }
 
-  let provider = Provider::<Http>::try_from(http_provider.clone())
+  let provider = connect_provider(&http_provider, &ConnectionOptions::default())
+    .await
     .with_context(|| "Failed to create provider".to_string())?;
 
   let signer_address = signer.address();
@@ -195,7 +199,7 @@ async fn fund_tokens(
 // use the root signer of the mnemonic to fund start_index to end_index wallet in the same mnemonic with per_holding
 async fn prefund(
   sender: LocalWallet,
-  mnemonic: String,
+  mnemonic: SignerSource,
   http_provider: String,
   start_index: usize,
   end_index: usize,
@@ -207,7 +211,7 @@ async fn prefund(
   let mut total_count = end_index - start_index;
   let batch_count = total_count / BATCH_SIZE;
 
-  let batch_count = if total_count % BATCH_SIZE != 0 {
+  let batch_count = if !total_count.is_multiple_of(BATCH_SIZE) {
     batch_count + 1
   } else {
     batch_count
@@ -225,12 +229,13 @@ async fn prefund(
     let start_index = start_index + n * BATCH_SIZE;
     let end_index = start_index + current_size - 1;
 
-    let addresses =
-      get_child_signers_with_range(&mnemonic, 1, start_index as i64, end_index as i64)
-        .await
-        .into_iter()
-        .map(|x| x.address())
-        .collect::<Vec<Address>>();
+    let range = AccountRange::new(start_index as i64, end_index as i64)?;
+    let addresses = mnemonic
+      .signers(1, range)
+      .await?
+      .into_iter()
+      .map(|x| x.address())
+      .collect::<Vec<Address>>();
 
     let holding_each = per_holding * BATCH_SIZE;
     total_count -= addresses.len();
@@ -266,21 +271,29 @@ async fn prefund(
 }
 
 pub async fn fund_erc20_tokens(
-  mnemonic: String,
-  random_mnemonic: String,
+  mnemonic: SignerSource,
+  random_mnemonic: SignerSource,
   http_provider: String,
   contract_address: String,
   token_address: String,
   total_sub_accounts: i64,
   per_holding: String,
+  partition: Option<AccountPartition>,
 ) -> Result<()> {
-  let provider = Provider::<Http>::try_from(http_provider.clone())
+  let partition = partition.map_or_else(|| single_worker(total_sub_accounts), Ok)?;
+  let accounts = partition.accounts()?;
+  let funders = partition.funders()?;
+
+  let provider = connect_provider(&http_provider, &ConnectionOptions::default())
+    .await
     .with_context(|| "Failed to create provider".to_string())?;
   let chain_id = provider
     .get_chainid()
     .await
     .with_context(|| "Failed to get chain id".to_string())?;
-  let root_wallet = get_child_signer(&mnemonic, 0, chain_id.low_u64());
+  let root_wallet = mnemonic
+    .signer(*funders.indexes().start(), chain_id.low_u64())
+    .await?;
 
   let contract_address: Address = contract_address
     .parse()
@@ -291,7 +304,7 @@ pub async fn fund_erc20_tokens(
   let per_holding =
     U256::from_dec_str(&per_holding).with_context(|| "Failed to parse per holding".to_string())?;
 
-  let number_of_transactions = total_sub_accounts as usize;
+  let number_of_transactions = accounts.len();
 
   let batch_count = number_of_transactions / BATCH_SIZE;
   let batch_count = if number_of_transactions % BATCH_SIZE != 0 {
@@ -300,10 +313,10 @@ pub async fn fund_erc20_tokens(
     batch_count
   };
 
-  let level_1_start_index = 1;
+  let level_1_start_index = *funders.indexes().start() as usize + 1;
   let level_1_end_index = level_1_start_index + batch_count - 1;
 
-  // fund signer from 1 to batch_count
+  // fund the signers of the other batches
   prefund(
     root_wallet,
     mnemonic.clone(),
@@ -319,18 +332,24 @@ pub async fn fund_erc20_tokens(
   .with_context(|| "Failed to prefund".to_string())?;
 
   let mut set = tokio::task::JoinSet::new();
-  let mut remain_accounts: usize = total_sub_accounts as usize;
+  let mut remain_accounts: usize = accounts.len();
 
-  let all_signers =
-    get_child_signers(&random_mnemonic, total_sub_accounts + 1, chain_id.low_u64()).await;
+  let all_signers = random_mnemonic
+    .signers(chain_id.low_u64(), accounts)
+    .await?;
 
   for batch_index in 0..batch_count {
     let all_signers = all_signers.clone();
     let http_provider = http_provider.clone();
 
-    let sender = get_child_signer(&mnemonic, batch_index as u32, chain_id.low_u64());
+    let sender = mnemonic
+      .signer(
+        funders.indexes().start() + batch_index as u32,
+        chain_id.low_u64(),
+      )
+      .await?;
 
-    let start_index = BATCH_SIZE * batch_index + 1;
+    let start_index = BATCH_SIZE * batch_index;
 
     let end_index = if remain_accounts < 200 {
       start_index + remain_accounts
@@ -8,10 +8,12 @@ use ethers::signers::LocalWallet;
 use crate::fund::batch_transfer_contract::{
   estimate_gas_native, BatchTransferV1Calls, MultisendNativeCall, BATCH_SIZE,
 };
-use crate::sign::signer::{get_child_signer, get_child_signers, get_child_signers_with_range};
+use crate::provider::{connect_provider, ConnectionOptions};
+use crate::sign::account_range::AccountRange;
+use crate::sign::partition::{single_worker, AccountPartition};
+use crate::sign::signer::SignerSource;
 use ethers::types::transaction::eip2718::TypedTransaction;
 use hex::ToHex;
-use napi::Error;
 
 async fn fund_tokens(
   signer: LocalWallet,
@@ -25,8 +27,8 @@ async fn fund_tokens(
     panic!("to_addresses length should be less than 200")
   }
 
-  let provider = Provider::<Http>::try_from(http_provider.clone())
-    .map_err(|e| Error::from_reason(format!("Can't not create provider: {}", e)))
+  let provider = connect_provider(&http_provider, &ConnectionOptions::default())
+    .await
     .with_context(|| "Failed to create provider".to_string())?;
 
   let signer_address = signer.address();
@@ -113,7 +115,7 @@ async fn fund_tokens(
 // use the root signer of the mnemonic to fund start_index to end_index wallet in the same mnemonic with per_holding
 async fn prefund(
   sender: LocalWallet,
-  mnemonic: String,
+  mnemonic: SignerSource,
   http_provider: String,
   start_index: usize,
   end_index: usize,
@@ -124,7 +126,7 @@ async fn prefund(
   let mut total_count = end_index - start_index;
   let batch_count = total_count / BATCH_SIZE;
 
-  let batch_count = if total_count % BATCH_SIZE != 0 {
+  let batch_count = if !total_count.is_multiple_of(BATCH_SIZE) {
     batch_count + 1
   } else {
     batch_count
@@ -133,9 +135,11 @@ async fn prefund(
     return Ok(());
   }
 
-  // estimate fee
-  let to_addresses = get_child_signers_with_range(&mnemonic, 1, 1, BATCH_SIZE as i64)
-    .await
+  // estimate fee with the first batch
+  let first_batch = AccountRange::with_len(start_index as i64, total_count.min(BATCH_SIZE) as i64)?;
+  let to_addresses = mnemonic
+    .signers(1, first_batch)
+    .await?
     .into_iter()
     .map(|x| x.address())
     .collect::<Vec<Address>>();
@@ -164,12 +168,13 @@ async fn prefund(
     let start_index = start_index + n * BATCH_SIZE;
     let end_index = start_index + current_size - 1;
 
-    let addresses =
-      get_child_signers_with_range(&mnemonic, 1, start_index as i64, end_index as i64)
-        .await
-        .into_iter()
-        .map(|x| x.address())
-        .collect::<Vec<Address>>();
+    let range = AccountRange::new(start_index as i64, end_index as i64)?;
+    let addresses = mnemonic
+      .signers(1, range)
+      .await?
+      .into_iter()
+      .map(|x| x.address())
+      .collect::<Vec<Address>>();
 
     // some redundancy fee for native batch transfer & erc20 batch transfer
     let holding_each = per_holding * BATCH_SIZE + estimate_gas.fee * 3;
@@ -203,23 +208,30 @@ async fn prefund(
 }
 
 pub async fn fund_native_tokens(
-  mnemonic: String,
-  random_mnemonic: String,
+  mnemonic: SignerSource,
+  random_mnemonic: SignerSource,
   http_provider: String,
   contract_address: String,
   total_sub_accounts: i64,
   per_holding: String,
+  partition: Option<AccountPartition>,
 ) -> Result<()> {
-  let provider = Provider::<Http>::try_from(http_provider.clone())
-    .map_err(|e| Error::from_reason(format!("Can't not create provider: {}", e)))
+  let partition = partition.map_or_else(|| single_worker(total_sub_accounts), Ok)?;
+  let accounts = partition.accounts()?;
+  let funders = partition.funders()?;
+
+  let provider = connect_provider(&http_provider, &ConnectionOptions::default())
+    .await
     .with_context(|| "Failed to create provider".to_string())?;
   let chain_id = provider
     .get_chainid()
     .await
     .with_context(|| "Failed to get chain id".to_string())?;
-  let root_wallet = get_child_signer(&mnemonic, 0, chain_id.low_u64());
+  let root_wallet = mnemonic
+    .signer(*funders.indexes().start(), chain_id.low_u64())
+    .await?;
 
-  let number_of_transactions = total_sub_accounts as usize;
+  let number_of_transactions = accounts.len();
 
   let batch_count = number_of_transactions / BATCH_SIZE;
   let batch_count = if number_of_transactions % BATCH_SIZE != 0 {
@@ -231,10 +243,10 @@ pub async fn fund_native_tokens(
   let per_holding =
     U256::from_dec_str(&per_holding).with_context(|| "Failed to parse per_holding".to_string())?;
 
-  let level_1_start_index = 1;
+  let level_1_start_index = *funders.indexes().start() as usize + 1;
   let level_1_end_index = level_1_start_index + batch_count - 1;
 
-  // fund signer from 1 to batch_count
+  // fund the signers of the other batches
   prefund(
     root_wallet,
     mnemonic.clone(),
@@ -249,19 +261,25 @@ pub async fn fund_native_tokens(
   .with_context(|| "Failed to fund first level".to_string())?;
 
   let mut set = tokio::task::JoinSet::new();
-  let mut remain_accounts: usize = total_sub_accounts as usize;
+  let mut remain_accounts: usize = accounts.len();
 
-  let all_signers =
-    get_child_signers(&random_mnemonic, total_sub_accounts + 1, chain_id.low_u64()).await;
+  let all_signers = random_mnemonic
+    .signers(chain_id.low_u64(), accounts)
+    .await?;
 
   for batch_index in 0..batch_count {
     let all_signers = all_signers.clone();
     let contract_address = contract_address.clone();
     let http_provider = http_provider.clone();
 
-    let sender = get_child_signer(&mnemonic, batch_index as u32, chain_id.low_u64());
+    let sender = mnemonic
+      .signer(
+        funders.indexes().start() + batch_index as u32,
+        chain_id.low_u64(),
+      )
+      .await?;
 
-    let start_index = BATCH_SIZE * batch_index + 1;
+    let start_index = BATCH_SIZE * batch_index;
 
     let end_index = if remain_accounts < 200 {
       start_index + remain_accounts
@@ -286,9 +304,9 @@ pub async fn fund_native_tokens(
       log::info!(
         "Level (2/2) round#{}: using account#{}, start_index: {}, end_index: {}",
         1 + batch_index,
-        batch_index,
-        start_index,
-        end_index - 1
+        *funders.indexes().start() as usize + batch_index,
+        *accounts.indexes().start() as usize + start_index,
+        *accounts.indexes().start() as usize + end_index - 1
       );
       fund_tokens(
         sender,
@@ -0,0 +1,471 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use ethers::providers::Middleware;
+use ethers::signers::coins_bip39::{English, Mnemonic};
+use ethers::signers::Signer;
+use rs_addon::{
+  aggregate, connect_provider, fund_erc20_tokens, fund_native_tokens, init_logger, load_keyring,
+  presign_transactions, replay_transactions, run_coordinator, run_worker, save_keyring,
+  send_raw_transactions, send_rounds, AccountRange, BenchmarkConfig, ConnectionOptions,
+  CoordinatorOptions, PresignOptions, RateControlOptions, ReplayOptions, RoundSpec, RoundStats,
+  SendOptions, SignerSource, WorkerOptions,
+};
+
+// value of every native transfer, same as the app
+const TRANSFER_VALUE: i64 = 100;
+
+/// Runs the benchmark engine without Node, driven by the same config-*.json as the app
+#[derive(Parser)]
+#[command(name = "rs-addon", version)]
+struct Cli {
+  /// Benchmark config
+  #[arg(short, long, default_value = "config.json")]
+  config: String,
+  /// Mnemonic of the benchmark accounts, overrides `account.random_mnemonic`
+  #[arg(long, global = true)]
+  random_mnemonic: Option<String>,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Fund accounts 1 to `total_tx` with native tokens through a batch transfer contract
+  FundNative {
+    #[arg(long)]
+    contract: String,
+    /// Wei per account, the config holding of the benchmark network by default
+    #[arg(long)]
+    per_holding: Option<String>,
+    /// Fund the accounts of that many `coordinator` workers, `total_tx` each
+    #[arg(long, default_value_t = 1)]
+    workers: u32,
+  },
+  /// Fund accounts 1 to `total_tx` with erc20 tokens through a batch transfer contract
+  FundErc20 {
+    #[arg(long)]
+    contract: String,
+    #[arg(long)]
+    token: String,
+    /// Token units per account
+    #[arg(long)]
+    per_holding: String,
+    /// Fund the accounts of that many `coordinator` workers, `total_tx` each
+    #[arg(long, default_value_t = 1)]
+    workers: u32,
+  },
+  /// Sign one transaction per account, written one raw transaction per line
+  Sign {
+    #[arg(long, default_value_t = 1)]
+    start_index: i64,
+    /// `total_tx` by default
+    #[arg(long)]
+    end_index: Option<i64>,
+    /// Fetched from the node by default
+    #[arg(long)]
+    chain_id: Option<u64>,
+    #[arg(short, long)]
+    output: String,
+  },
+  /// Send raw transactions, one per line, as a single round
+  Send {
+    input: String,
+    #[command(flatten)]
+    rate: RateArgs,
+    /// Appends the round stats as a json line
+    #[arg(long)]
+    stats: Option<String>,
+  },
+  /// Sign the rounds of `run` to a file, to replay them later
+  Presign {
+    #[arg(long)]
+    chain_id: Option<u64>,
+    #[arg(short, long)]
+    output: String,
+    /// `jsonl` or `binary`, `jsonl` for .jsonl outputs and `binary` otherwise by default
+    #[arg(long)]
+    format: Option<String>,
+  },
+  /// Send presigned rounds, one every `every_n_second`
+  Replay {
+    input: String,
+    /// `jsonl` or `binary`, `jsonl` for .jsonl inputs and `binary` otherwise by default
+    #[arg(long)]
+    format: Option<String>,
+    #[command(flatten)]
+    rate: RateArgs,
+    /// Appends the stats of every round as json lines
+    #[arg(long)]
+    stats: Option<String>,
+  },
+  /// Sign and send `total_tx` transactions, `txs_per_batch` every `every_n_second` like the app
+  Run {
+    #[arg(long)]
+    chain_id: Option<u64>,
+    /// Appends the stats of every round as json lines
+    #[arg(long)]
+    stats: Option<String>,
+  },
+  /// Run `run` on several workers at once: every worker sends `txs_per_batch` per round from
+  /// accounts of its own, the rounds start on all of them together
+  Coordinator {
+    #[arg(long, default_value = "0.0.0.0:7070")]
+    listen: String,
+    #[arg(long)]
+    workers: u32,
+    /// Appends the stats of every round, over all workers, as json lines
+    #[arg(long)]
+    stats: Option<String>,
+  },
+  /// Join a coordinator, sign the rounds of the accounts it assigns and send them on its signal
+  Worker {
+    /// Address of the coordinator, e.g. `10.0.0.1:7070`
+    coordinator: String,
+    #[arg(long)]
+    chain_id: Option<u64>,
+    #[command(flatten)]
+    rate: RateArgs,
+  },
+}
+
+#[derive(Args)]
+struct RateArgs {
+  /// Fixed send rate within a round, as fast as possible by default
+  #[arg(long)]
+  tps: Option<f64>,
+  #[arg(long)]
+  max_in_flight: Option<u32>,
+}
+
+impl RateArgs {
+  fn send_options(&self) -> SendOptions {
+    SendOptions {
+      rate_control: Some(RateControlOptions {
+        mode: self.tps.map(|_| "fixed-load".to_string()),
+        tps: self.tps,
+        start_tps: None,
+        max_in_flight: self.max_in_flight,
+      }),
+      ..Default::default()
+    }
+  }
+}
+
+struct Bench {
+  config: BenchmarkConfig,
+  random_mnemonic: Option<String>,
+}
+
+impl Bench {
+  fn node_url(&self) -> Result<&str> {
+    Ok(&self.config.benchmark_network()?.node_url)
+  }
+
+  // benchmark accounts, a new mnemonic for funding when none is given. Later runs need it, so
+  // it's logged
+  fn random_mnemonic(&self, generate: bool) -> Result<String> {
+    if let Some(mnemonic) = self.random_mnemonic.clone() {
+      return Ok(mnemonic);
+    }
+    if !generate {
+      bail!("the benchmark accounts need `--random-mnemonic` or `account.random_mnemonic`");
+    }
+    let mnemonic = Mnemonic::<English>::new(&mut ethers::core::rand::thread_rng()).to_phrase();
+    log::info!("random generated mnemonic: {}", mnemonic);
+    Ok(mnemonic)
+  }
+
+  async fn chain_id(&self, chain_id: Option<u64>) -> Result<u64> {
+    if let Some(chain_id) = chain_id {
+      return Ok(chain_id);
+    }
+    let provider = connect_provider(self.node_url()?, &ConnectionOptions::default()).await?;
+    Ok(provider.get_chainid().await?.as_u64())
+  }
+
+  // gas price and limit as 0x prefixed hex, the builders parse them as hex either way
+  fn gas(&self) -> Result<(String, String)> {
+    let network = self.config.benchmark_network()?;
+    Ok((
+      format!("{:#x}", network.gas_price()?),
+      format!("{:#x}", network.gas_limit()?),
+    ))
+  }
+
+  async fn load_keyring(&self) -> Result<()> {
+    if let Some(keyring) = &self.config.account.keyring {
+      if Path::new(&keyring.path).exists() {
+        let loaded = load_keyring(keyring.path.clone(), keyring.password.clone()).await?;
+        log::info!("[keyring] loaded {} keys from {}", loaded, keyring.path);
+      }
+    }
+    Ok(())
+  }
+
+  async fn save_keyring(&self) -> Result<()> {
+    if let Some(keyring) = &self.config.account.keyring {
+      let saved = save_keyring(keyring.path.clone(), keyring.password.clone()).await?;
+      log::info!("[keyring] saved {} keys to {}", saved, keyring.path);
+    }
+    Ok(())
+  }
+
+  async fn round_spec(&self, chain_id: u64) -> Result<RoundSpec> {
+    let (gas_price, gas_limit) = self.gas()?;
+    let mut spec = RoundSpec {
+      accounts: self.random_mnemonic(false)?.into(),
+      chain_id: chain_id as i64,
+      gas_price,
+      gas_limit,
+      to_address: None,
+      value: None,
+      workload_path: None,
+      tx_options: None,
+    };
+
+    match self.config.tx_type.as_str() {
+      "normalTransfer" => {
+        // the root funder gets the transfers back
+        let root = SignerSource::from(self.config.account.mnemonic.clone())
+          .signer(0, chain_id)
+          .await?;
+        spec.to_address = Some(format!("{:?}", root.address()));
+        spec.value = Some(TRANSFER_VALUE);
+      }
+      "workload" => {
+        let Some(workload) = self.config.workload.clone() else {
+          bail!("the workload tx type needs `workload`");
+        };
+        spec.workload_path = Some(workload);
+      }
+      tx_type => bail!(
+        "tx type {} needs contracts deployed by the app, use `workload` instead",
+        tx_type
+      ),
+    }
+    Ok(spec)
+  }
+
+  // `total_tx` transactions from account 1 in rounds of `txs_per_batch`, like the app
+  fn rounds(&self) -> Vec<u32> {
+    let batch = self.config.rate_control.txs_per_batch.max(1);
+    let total_tx = self.config.total_tx;
+    (0..total_tx.div_ceil(batch))
+      .map(|round| batch.min(total_tx - round * batch))
+      .collect()
+  }
+}
+
+fn write_stats(path: &Option<String>, stats: &RoundStats) -> Result<()> {
+  log::info!(
+    "{} transactions | {} success | {} reverted | {} failed | {:.2} tps",
+    stats.total,
+    stats.success,
+    stats.reverted,
+    stats.failed,
+    stats.tps
+  );
+  if let Some(path) = path {
+    let mut file = std::fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(path)
+      .with_context(|| format!("Failed to open stats: {}", path))?;
+    writeln!(file, "{}", serde_json::to_string(stats)?)?;
+  }
+  Ok(())
+}
+
+async fn run(cli: Cli) -> Result<()> {
+  let config = BenchmarkConfig::load(&cli.config)?;
+  let bench = Bench {
+    random_mnemonic: cli
+      .random_mnemonic
+      .or_else(|| config.account.random_mnemonic.clone()),
+    config,
+  };
+  let total_tx = bench.config.total_tx as i64;
+
+  match cli.command {
+    Command::FundNative {
+      contract,
+      per_holding,
+      workers,
+    } => {
+      let per_holding = match per_holding {
+        Some(per_holding) => per_holding,
+        None => bench.config.per_holding()?.to_string(),
+      };
+      bench.load_keyring().await?;
+      fund_native_tokens(
+        bench.config.account.mnemonic.clone().into(),
+        bench.random_mnemonic(true)?.into(),
+        bench.node_url()?.to_string(),
+        contract,
+        total_tx * workers as i64,
+        per_holding,
+        None,
+      )
+      .await?;
+      bench.save_keyring().await?;
+    }
+    Command::FundErc20 {
+      contract,
+      token,
+      per_holding,
+      workers,
+    } => {
+      bench.load_keyring().await?;
+      fund_erc20_tokens(
+        bench.config.account.mnemonic.clone().into(),
+        bench.random_mnemonic(false)?.into(),
+        bench.node_url()?.to_string(),
+        contract,
+        token,
+        total_tx * workers as i64,
+        per_holding,
+        None,
+      )
+      .await?;
+      bench.save_keyring().await?;
+    }
+    Command::Sign {
+      start_index,
+      end_index,
+      chain_id,
+      output,
+    } => {
+      bench.load_keyring().await?;
+      let chain_id = bench.chain_id(chain_id).await?;
+      let accounts = AccountRange::new(start_index, end_index.unwrap_or(total_tx))?;
+      let signed = bench.round_spec(chain_id).await?.build(accounts).await?;
+      let mut content = signed.join("\n");
+      content.push('\n');
+      std::fs::write(&output, content).with_context(|| format!("Failed to write {}", output))?;
+      log::info!("{} signed transactions written to {}", signed.len(), output);
+    }
+    Command::Send { input, rate, stats } => {
+      let content =
+        std::fs::read_to_string(&input).with_context(|| format!("Failed to read {}", input))?;
+      let transactions: Vec<String> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+      let results = send_raw_transactions(
+        bench.node_url()?.to_string(),
+        transactions,
+        rate.send_options(),
+      )
+      .await?;
+      write_stats(&stats, &aggregate(&results, None))?;
+    }
+    Command::Presign {
+      chain_id,
+      output,
+      format,
+    } => {
+      bench.load_keyring().await?;
+      let chain_id = bench.chain_id(chain_id).await?;
+      let count = presign_transactions(PresignOptions {
+        path: output.clone(),
+        format,
+        transactions: bench.round_spec(chain_id).await?,
+        start_index: 1,
+        rounds: bench.rounds(),
+      })
+      .await?;
+      log::info!("{} signed transactions written to {}", count, output);
+    }
+    Command::Replay {
+      input,
+      format,
+      rate,
+      stats,
+    } => {
+      let options = ReplayOptions {
+        format,
+        send_options: Some(rate.send_options()),
+        round_interval_ms: Some(bench.config.rate_control.every_n_second * 1000),
+        bucket_ms: None,
+      };
+      for round_stats in replay_transactions(bench.node_url()?.to_string(), input, options).await? {
+        write_stats(&stats, &round_stats)?;
+      }
+    }
+    Command::Run { chain_id, stats } => {
+      bench.load_keyring().await?;
+      let chain_id = bench.chain_id(chain_id).await?;
+      let spec = bench.round_spec(chain_id).await?;
+
+      // signing is done up front, so it doesn't slow the rounds down
+      let mut rounds = vec![];
+      let mut start_index = 1;
+      for size in bench.rounds() {
+        let accounts = AccountRange::with_len(start_index, size as i64)?;
+        log::info!(
+          "Building transactions | Account Index {} - {}",
+          accounts.indexes().start(),
+          accounts.indexes().end()
+        );
+        rounds.push(spec.build(accounts).await?);
+        start_index += size as i64;
+      }
+
+      // rounds start on their tick and may overlap, like the app's schedule
+      let options = ReplayOptions {
+        round_interval_ms: Some(bench.config.rate_control.every_n_second * 1000),
+        ..Default::default()
+      };
+      for round_stats in send_rounds(bench.node_url()?.to_string(), rounds, options).await? {
+        write_stats(&stats, &round_stats)?;
+      }
+    }
+    Command::Coordinator {
+      listen,
+      workers,
+      stats,
+    } => {
+      let options = CoordinatorOptions {
+        listen_address: listen,
+        workers,
+        start_index: 1,
+        end_index: total_tx * workers as i64,
+        rounds: bench.rounds(),
+        start_delay_ms: None,
+        bucket_ms: None,
+      };
+      for round_stats in run_coordinator(options).await? {
+        write_stats(&stats, &round_stats)?;
+      }
+    }
+    Command::Worker {
+      coordinator,
+      chain_id,
+      rate,
+    } => {
+      bench.load_keyring().await?;
+      let chain_id = bench.chain_id(chain_id).await?;
+      let options = WorkerOptions {
+        coordinator_address: coordinator.clone(),
+        http_provider: bench.node_url()?.to_string(),
+        transactions: bench.round_spec(chain_id).await?,
+        send_options: Some(rate.send_options()),
+      };
+      let count = run_worker(&coordinator, &options).await?;
+      log::info!("{} rounds sent", count);
+    }
+  }
+
+  Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  init_logger();
+  run(Cli::parse()).await
+}
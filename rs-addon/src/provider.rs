@@ -0,0 +1,138 @@
+use std::fmt::Debug;
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::providers::{Http, Ipc, JsonRpcClient, Provider, ProviderError, PubsubClient, Ws};
+use ethers::types::U256;
+use futures_util::Stream;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
+
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: u32 = 512;
+const DEFAULT_POOL_IDLE_TIMEOUT_MS: u32 = 90_000;
+const DEFAULT_CONNECT_TIMEOUT_MS: u32 = 10_000;
+const DEFAULT_REQUEST_TIMEOUT_MS: u32 = 30_000;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct ConnectionOptions {
+  pub pool_max_idle_per_host: Option<u32>,
+  pub pool_idle_timeout_ms: Option<u32>,
+  pub connect_timeout_ms: Option<u32>,
+  pub request_timeout_ms: Option<u32>,
+}
+
+// picked from the url scheme: `http(s)://`, `ws(s)://`, or `ipc://` / a plain socket path
+#[derive(Debug, Clone)]
+pub enum Transport {
+  Http(Http),
+  Ws(Ws),
+  Ipc(Ipc),
+}
+
+impl Transport {
+  pub fn supports_subscriptions(&self) -> bool {
+    !matches!(self, Transport::Http(_))
+  }
+}
+
+#[async_trait]
+impl JsonRpcClient for Transport {
+  type Error = ProviderError;
+
+  async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+  where
+    T: Debug + Serialize + Send + Sync,
+    R: DeserializeOwned + Send,
+  {
+    match self {
+      Transport::Http(http) => http.request(method, params).await.map_err(Into::into),
+      Transport::Ws(ws) => ws.request(method, params).await.map_err(Into::into),
+      Transport::Ipc(ipc) => ipc.request(method, params).await.map_err(Into::into),
+    }
+  }
+}
+
+impl PubsubClient for Transport {
+  type NotificationStream = Pin<Box<dyn Stream<Item = Box<RawValue>> + Send>>;
+
+  fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+    match self {
+      Transport::Http(_) => Err(ProviderError::UnsupportedRPC),
+      Transport::Ws(ws) => Ok(Box::pin(ws.subscribe(id)?)),
+      Transport::Ipc(ipc) => Ok(Box::pin(ipc.subscribe(id)?)),
+    }
+  }
+
+  fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+    match self {
+      Transport::Http(_) => Err(ProviderError::UnsupportedRPC),
+      Transport::Ws(ws) => Ok(ws.unsubscribe(id)?),
+      Transport::Ipc(ipc) => Ok(ipc.unsubscribe(id)?),
+    }
+  }
+}
+
+// connect once per endpoint and clone the provider into every task, so all requests share the
+// same connection (pool)
+pub async fn connect_provider(
+  url: &str,
+  options: &ConnectionOptions,
+) -> Result<Provider<Transport>> {
+  let transport = if url.starts_with("http://") || url.starts_with("https://") {
+    Transport::Http(build_http_transport(url, options)?)
+  } else if url.starts_with("ws://") || url.starts_with("wss://") {
+    let ws = Ws::connect(url)
+      .await
+      .with_context(|| format!("Failed to connect websocket: {}", url))?;
+    Transport::Ws(ws)
+  } else if !url.contains("://") || url.starts_with("ipc://") {
+    let path = url.trim_start_matches("ipc://");
+    let ipc = Ipc::connect(path)
+      .await
+      .with_context(|| format!("Failed to connect ipc socket: {}", path))?;
+    Transport::Ipc(ipc)
+  } else {
+    anyhow::bail!("Unsupported provider url: {}", url);
+  };
+
+  Ok(Provider::new(transport))
+}
+
+// keep-alive http client with a bounded connection pool
+fn build_http_transport(url: &str, options: &ConnectionOptions) -> Result<Http> {
+  let url = url
+    .parse::<reqwest::Url>()
+    .with_context(|| format!("Failed to parse provider url: {}", url))?;
+
+  let client = reqwest::Client::builder()
+    .pool_max_idle_per_host(
+      options
+        .pool_max_idle_per_host
+        .unwrap_or(DEFAULT_POOL_MAX_IDLE_PER_HOST) as usize,
+    )
+    .pool_idle_timeout(Duration::from_millis(
+      options
+        .pool_idle_timeout_ms
+        .unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT_MS) as u64,
+    ))
+    .connect_timeout(Duration::from_millis(
+      options
+        .connect_timeout_ms
+        .unwrap_or(DEFAULT_CONNECT_TIMEOUT_MS) as u64,
+    ))
+    .timeout(Duration::from_millis(
+      options
+        .request_timeout_ms
+        .unwrap_or(DEFAULT_REQUEST_TIMEOUT_MS) as u64,
+    ))
+    .tcp_keepalive(TCP_KEEPALIVE)
+    .tcp_nodelay(true)
+    .build()
+    .with_context(|| "Failed to build http client".to_string())?;
+
+  Ok(Http::new_with_client(url, client))
+}
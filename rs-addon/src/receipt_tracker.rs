@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use ethers::providers::{Middleware, Provider, ProviderError, RpcError, StreamExt};
+use ethers::types::{TransactionReceipt, H256, U256, U64};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::provider::Transport;
+
+pub const DEFAULT_BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const METHOD_NOT_FOUND: i64 = -32601;
+
+pub struct TrackedReceipt {
+  pub receipt: TransactionReceipt,
+  pub block_timestamp: U256,
+  // when the block completing the required confirmations was seen by the tracker
+  pub observed_at: Instant,
+}
+
+type Pending = Arc<Mutex<HashMap<H256, oneshot::Sender<TrackedReceipt>>>>;
+
+// mined but not yet confirmed, released once the chain reaches `confirmed_at`
+struct Confirming {
+  confirmed_at: U64,
+  sender: oneshot::Sender<TrackedReceipt>,
+  receipt: TransactionReceipt,
+  block_timestamp: U256,
+}
+
+// Follows the chain head once for a whole round (`newHeads` on ws/ipc, block number polling on
+// http) and resolves every tracked hash from the receipts of new blocks, instead of polling
+// `eth_getTransactionReceipt` per transaction.
+pub struct ReceiptTracker {
+  pending: Pending,
+  handle: JoinHandle<()>,
+}
+
+impl ReceiptTracker {
+  // `confirmations` counts the including block, so 1 resolves as soon as the tx is mined
+  pub async fn start(
+    provider: Provider<Transport>,
+    poll_interval: Duration,
+    confirmations: u64,
+  ) -> Result<Self> {
+    let next_block = provider
+      .get_block_number()
+      .await
+      .with_context(|| "Failed to get block number".to_string())?;
+
+    let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+    let follower = BlockFollower {
+      provider,
+      pending: pending.clone(),
+      next_block,
+      block_receipts_supported: true,
+      confirmations: confirmations.max(1),
+      confirming: vec![],
+    };
+    let handle = tokio::spawn(follower.run(poll_interval));
+
+    Ok(ReceiptTracker { pending, handle })
+  }
+
+  // must be called before the transaction is sent, so it can't be mined before being tracked
+  pub fn track(&self, hash: H256) -> oneshot::Receiver<TrackedReceipt> {
+    let (sender, receiver) = oneshot::channel();
+    self.pending.lock().unwrap().insert(hash, sender);
+    receiver
+  }
+
+  pub fn untrack(&self, hash: &H256) {
+    self.pending.lock().unwrap().remove(hash);
+  }
+}
+
+impl Drop for ReceiptTracker {
+  fn drop(&mut self) {
+    self.handle.abort();
+  }
+}
+
+struct BlockFollower {
+  provider: Provider<Transport>,
+  pending: Pending,
+  next_block: U64,
+  block_receipts_supported: bool,
+  confirmations: u64,
+  confirming: Vec<Confirming>,
+}
+
+impl BlockFollower {
+  async fn run(mut self, poll_interval: Duration) {
+    if self.provider.as_ref().supports_subscriptions() {
+      self.follow_new_heads().await;
+    }
+    self.poll_block_number(poll_interval).await;
+  }
+
+  async fn follow_new_heads(&mut self) {
+    let provider = self.provider.clone();
+    let mut heads = match provider.subscribe_blocks().await {
+      Ok(heads) => heads,
+      Err(e) => {
+        log::warn!(
+          "[receipt tracker] failed to subscribe newHeads, falling back to polling: {}",
+          e
+        );
+        return;
+      }
+    };
+
+    while let Some(head) = heads.next().await {
+      if let Some(number) = head.number {
+        self.catch_up(number).await;
+      }
+    }
+
+    log::warn!("[receipt tracker] newHeads subscription closed, falling back to polling");
+  }
+
+  async fn poll_block_number(&mut self, poll_interval: Duration) {
+    let mut interval = tokio::time::interval(poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+      interval.tick().await;
+
+      match self.provider.get_block_number().await {
+        Ok(latest) => self.catch_up(latest).await,
+        Err(e) => log::warn!("[receipt tracker] failed to get block number: {}", e),
+      }
+    }
+  }
+
+  async fn catch_up(&mut self, latest: U64) {
+    while self.next_block <= latest {
+      match self.process_block(self.next_block).await {
+        Ok(()) => self.next_block += U64::one(),
+        Err(e) => {
+          // retry the same block on the next head
+          log::warn!("[receipt tracker] block #{}: {:#}", self.next_block, e);
+          break;
+        }
+      }
+    }
+  }
+
+  async fn process_block(&mut self, block_number: U64) -> Result<()> {
+    let observed_at = Instant::now();
+    let block = self
+      .provider
+      .get_block(block_number)
+      .await
+      .with_context(|| "Failed to get block".to_string())?
+      .with_context(|| "Block not found".to_string())?;
+
+    let tracked_hashes: Vec<H256> = {
+      let pending = self.pending.lock().unwrap();
+      block
+        .transactions
+        .iter()
+        .filter(|hash| pending.contains_key(hash))
+        .cloned()
+        .collect()
+    };
+
+    if !tracked_hashes.is_empty() {
+      let receipts = self.fetch_receipts(block_number, &tracked_hashes).await?;
+
+      let mut pending = self.pending.lock().unwrap();
+      for receipt in receipts {
+        if let Some(sender) = pending.remove(&receipt.transaction_hash) {
+          self.confirming.push(Confirming {
+            confirmed_at: block_number + self.confirmations - 1,
+            sender,
+            receipt,
+            block_timestamp: block.timestamp,
+          });
+        }
+      }
+    }
+
+    self.release_confirmed(block_number, observed_at);
+
+    Ok(())
+  }
+
+  fn release_confirmed(&mut self, block_number: U64, observed_at: Instant) {
+    let (confirmed, confirming) = std::mem::take(&mut self.confirming)
+      .into_iter()
+      .partition(|x| x.confirmed_at <= block_number);
+    self.confirming = confirming;
+
+    for Confirming {
+      sender,
+      receipt,
+      block_timestamp,
+      ..
+    } in confirmed
+    {
+      _ = sender.send(TrackedReceipt {
+        receipt,
+        block_timestamp,
+        observed_at,
+      });
+    }
+  }
+
+  // prefer `eth_getBlockReceipts`, fall back to one receipt request per tracked transaction
+  // of the block on nodes that don't support it
+  async fn fetch_receipts(
+    &mut self,
+    block_number: U64,
+    tracked_hashes: &[H256],
+  ) -> Result<Vec<TransactionReceipt>> {
+    if self.block_receipts_supported {
+      match self.provider.get_block_receipts(block_number).await {
+        Ok(receipts) => return Ok(receipts),
+        Err(e) if is_method_unsupported(&e) => {
+          log::warn!(
+            "[receipt tracker] eth_getBlockReceipts unavailable, falling back to per tx receipts: {}",
+            e
+          );
+          self.block_receipts_supported = false;
+        }
+        // timeouts, rate limits and the like only fall back for this block
+        Err(e) => log::warn!(
+          "[receipt tracker] eth_getBlockReceipts failed on block #{}, falling back to per tx receipts: {}",
+          block_number,
+          e
+        ),
+      }
+    }
+
+    let mut receipts = Vec::with_capacity(tracked_hashes.len());
+    for hash in tracked_hashes {
+      let receipt = self
+        .provider
+        .get_transaction_receipt(*hash)
+        .await
+        .with_context(|| format!("Failed to get receipt, tx: {:?}", hash))?
+        .with_context(|| format!("Receipt not found, tx: {:?}", hash))?;
+      receipts.push(receipt);
+    }
+
+    Ok(receipts)
+  }
+}
+
+// the node doesn't implement the method at all, as opposed to failing this one request
+fn is_method_unsupported(error: &ProviderError) -> bool {
+  let Some(response) = error.as_error_response() else {
+    return matches!(error, ProviderError::UnsupportedRPC);
+  };
+  let message = response.message.to_lowercase();
+  response.code == METHOD_NOT_FOUND
+    || (message.contains("method")
+      && [
+        "not found",
+        "does not exist",
+        "not supported",
+        "unsupported",
+        "not available",
+      ]
+      .iter()
+      .any(|phrase| message.contains(phrase)))
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::providers::{HttpClientError, JsonRpcError, ProviderError};
+
+  use crate::receipt_tracker::is_method_unsupported;
+
+  fn rpc_error(code: i64, message: &str) -> ProviderError {
+    HttpClientError::JsonRpcError(JsonRpcError {
+      code,
+      message: message.to_string(),
+      data: None,
+    })
+    .into()
+  }
+
+  #[test]
+  fn test_is_method_unsupported() {
+    assert!(is_method_unsupported(&rpc_error(
+      -32601,
+      "Method not found"
+    )));
+    assert!(is_method_unsupported(&rpc_error(
+      -32000,
+      "the method eth_getBlockReceipts does not exist/is not available"
+    )));
+    assert!(is_method_unsupported(&ProviderError::UnsupportedRPC));
+
+    assert!(!is_method_unsupported(&rpc_error(429, "Too Many Requests")));
+    assert!(!is_method_unsupported(&rpc_error(
+      -32000,
+      "request timed out"
+    )));
+    assert!(!is_method_unsupported(&ProviderError::CustomError(
+      "connection reset".to_string()
+    )));
+  }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::sign::presign::{read_presigned, PresignFormat};
+use crate::stats::{aggregate, RoundStats};
+use crate::transaction::{send_raw_transactions, SendOptions};
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct ReplayOptions {
+  // `jsonl` or `binary`, told from the path by default
+  pub format: Option<String>,
+  // rate control applies within every round
+  pub send_options: Option<SendOptions>,
+  // rounds start this far apart and may overlap, by default a round starts once the previous one
+  // is done
+  pub round_interval_ms: Option<u32>,
+  pub bucket_ms: Option<u32>,
+}
+
+// sends the rounds presigned at `path`, returns the stats of every round. The whole file is read
+// before the first send, so reading doesn't skew the rounds
+pub async fn replay_transactions(
+  http_provider: String,
+  path: String,
+  options: ReplayOptions,
+) -> Result<Vec<RoundStats>> {
+  let format = PresignFormat::new(options.format.as_deref(), &path)?;
+  let rounds = read_presigned(&path, format)?;
+  log::info!("replaying {} rounds from {}", rounds.len(), path);
+
+  send_rounds(http_provider, rounds, options).await
+}
+
+pub async fn send_rounds(
+  http_provider: String,
+  rounds: Vec<Vec<String>>,
+  options: ReplayOptions,
+) -> Result<Vec<RoundStats>> {
+  let send_options = options.send_options.unwrap_or_default();
+  let mut stats = Vec::with_capacity(rounds.len());
+
+  let Some(interval_ms) = options.round_interval_ms else {
+    for (round, transactions) in rounds.into_iter().enumerate() {
+      log::info!(
+        "round {} | sending {} transactions",
+        round,
+        transactions.len()
+      );
+      let results =
+        send_raw_transactions(http_provider.clone(), transactions, send_options.clone()).await?;
+      stats.push(aggregate(&results, options.bucket_ms));
+    }
+    return Ok(stats);
+  };
+
+  let interval = Duration::from_millis(interval_ms as u64);
+  let start = tokio::time::Instant::now();
+  let mut handles = Vec::with_capacity(rounds.len());
+  for (round, transactions) in rounds.into_iter().enumerate() {
+    tokio::time::sleep_until(start + interval * round as u32).await;
+    log::info!(
+      "round {} | sending {} transactions",
+      round,
+      transactions.len()
+    );
+    handles.push(tokio::spawn(send_raw_transactions(
+      http_provider.clone(),
+      transactions,
+      send_options.clone(),
+    )));
+  }
+  for handle in handles {
+    stats.push(aggregate(&handle.await??, options.bucket_ms));
+  }
+
+  Ok(stats)
+}
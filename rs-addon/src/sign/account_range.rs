@@ -0,0 +1,118 @@
+use std::ops::RangeInclusive;
+
+use anyhow::{bail, Result};
+
+// child indexes from 2^31 up are hardened, accounts are derived as normal children
+pub const MAX_ACCOUNT_INDEX: i64 = (1 << 31) - 1;
+// accounts derived in a single call, bounds the memory and time spent on one range
+pub const MAX_ACCOUNT_RANGE_LEN: i64 = 10_000_000;
+
+// inclusive range of derived account indexes, checked once so builders can zip their signers with
+// `indexes` instead of indexing them by account index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountRange {
+  start: u32,
+  end: u32,
+}
+
+impl AccountRange {
+  pub fn new(start_index: i64, end_index: i64) -> Result<Self> {
+    if start_index < 0 || end_index < 0 {
+      bail!(
+        "account indexes should not be negative, got {} - {}",
+        start_index,
+        end_index
+      );
+    }
+    if start_index > end_index {
+      bail!(
+        "start index {} is greater than end index {}",
+        start_index,
+        end_index
+      );
+    }
+    if end_index > MAX_ACCOUNT_INDEX {
+      bail!(
+        "end index {} is greater than the max account index {}",
+        end_index,
+        MAX_ACCOUNT_INDEX
+      );
+    }
+    if end_index - start_index + 1 > MAX_ACCOUNT_RANGE_LEN {
+      bail!(
+        "range {} - {} has more than {} accounts",
+        start_index,
+        end_index,
+        MAX_ACCOUNT_RANGE_LEN
+      );
+    }
+
+    Ok(AccountRange {
+      start: start_index as u32,
+      end: end_index as u32,
+    })
+  }
+
+  // `len` accounts from `start_index`
+  pub fn with_len(start_index: i64, len: i64) -> Result<Self> {
+    if len < 1 {
+      bail!("account range should have at least 1 account, got {}", len);
+    }
+    Self::new(start_index, start_index.saturating_add(len - 1))
+  }
+
+  // the same number of accounts, `offset` indexes later
+  pub fn offset(&self, offset: u32) -> Result<Self> {
+    Self::new(
+      self.start as i64 + offset as i64,
+      self.end as i64 + offset as i64,
+    )
+  }
+
+  // ranges hold at least one account, so there's no `is_empty`
+  #[allow(clippy::len_without_is_empty)]
+  pub fn len(&self) -> usize {
+    (self.end - self.start) as usize + 1
+  }
+
+  pub fn indexes(&self) -> RangeInclusive<u32> {
+    self.start..=self.end
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::sign::account_range::{AccountRange, MAX_ACCOUNT_INDEX, MAX_ACCOUNT_RANGE_LEN};
+
+  #[test]
+  fn test_account_range() {
+    let range = AccountRange::new(5, 7).unwrap();
+    assert_eq!(range.len(), 3);
+    assert_eq!(range.indexes().collect::<Vec<_>>(), vec![5, 6, 7]);
+
+    assert_eq!(AccountRange::new(0, 0).unwrap().len(), 1);
+    assert_eq!(
+      AccountRange::with_len(3, 2).unwrap(),
+      AccountRange::new(3, 4).unwrap()
+    );
+    assert_eq!(
+      range.offset(10).unwrap(),
+      AccountRange::new(15, 17).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_invalid_account_range() {
+    assert!(AccountRange::new(-1, 3).is_err());
+    assert!(AccountRange::new(4, 3).is_err());
+    assert!(AccountRange::new(0, MAX_ACCOUNT_INDEX + 1).is_err());
+    assert!(AccountRange::new(0, MAX_ACCOUNT_RANGE_LEN).is_err());
+    assert!(AccountRange::new(1, MAX_ACCOUNT_RANGE_LEN).is_ok());
+    assert!(AccountRange::with_len(0, 0).is_err());
+    assert!(AccountRange::with_len(i64::MAX, 2).is_err());
+    assert!(AccountRange::new(0, 1)
+      .unwrap()
+      .offset(MAX_ACCOUNT_INDEX as u32)
+      .is_err());
+  }
+}
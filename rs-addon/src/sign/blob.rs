@@ -0,0 +1,393 @@
+use anyhow::{bail, Context, Result};
+use c_kzg::{
+  ethereum_kzg_settings, Blob, KzgCommitment, KzgProof, BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT,
+};
+use ethers::core::rand::{thread_rng, RngCore};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use ethers::utils::rlp::RlpStream;
+use sha2::{Digest, Sha256};
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::signature::non_negative;
+use crate::sign::signer::SignerSource;
+
+pub const BLOB_TX_TYPE: u8 = 0x03;
+// cancun limit of blobs per block, a single tx can't carry more
+pub const MAX_BLOBS_PER_TX: u32 = 6;
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct BlobOptions {
+  // 1 (default) to 6
+  pub blobs_per_tx: Option<u32>,
+  // "random" (default), "zero", or a 0x-prefixed hex pattern repeated over the blob
+  pub fill: Option<String>,
+  // defaults to gas price
+  pub max_fee_per_gas: Option<String>,
+  // defaults to max fee per gas
+  pub max_priority_fee_per_gas: Option<String>,
+  // defaults to gas price
+  pub max_fee_per_blob_gas: Option<String>,
+}
+
+enum BlobFill {
+  Random,
+  Pattern(Vec<u8>),
+}
+
+impl BlobFill {
+  fn from_option(fill: Option<String>) -> Result<Self> {
+    match fill.as_deref().unwrap_or("random") {
+      "random" => Ok(BlobFill::Random),
+      "zero" => Ok(BlobFill::Pattern(vec![0])),
+      pattern => {
+        let pattern = pattern
+          .strip_prefix("0x")
+          .with_context(|| format!("Unsupported blob fill: {}", pattern))?;
+        let pattern =
+          hex::decode(pattern).with_context(|| "Failed to decode blob fill pattern".to_string())?;
+        if pattern.is_empty() {
+          bail!("blob fill pattern should not be empty");
+        }
+        Ok(BlobFill::Pattern(pattern))
+      }
+    }
+  }
+
+  // every field element must stay below the bls modulus, so its leading byte is kept zero and
+  // the payload only fills the remaining 31 bytes
+  fn blob(&self) -> Result<Box<Blob>> {
+    let mut payload = vec![0u8; BYTES_PER_BLOB / BYTES_PER_FIELD_ELEMENT * 31];
+    match self {
+      BlobFill::Random => thread_rng().fill_bytes(&mut payload),
+      BlobFill::Pattern(pattern) => {
+        for (byte, value) in payload.iter_mut().zip(pattern.iter().cycle()) {
+          *byte = *value;
+        }
+      }
+    }
+
+    let mut bytes = vec![0u8; BYTES_PER_BLOB];
+    for (element, chunk) in bytes
+      .chunks_mut(BYTES_PER_FIELD_ELEMENT)
+      .zip(payload.chunks(31))
+    {
+      element[1..].copy_from_slice(chunk);
+    }
+
+    Ok(Box::new(
+      Blob::from_bytes(&bytes).with_context(|| "Failed to build blob".to_string())?,
+    ))
+  }
+}
+
+pub struct BlobSidecar {
+  pub blobs: Vec<Box<Blob>>,
+  pub commitments: Vec<KzgCommitment>,
+  pub proofs: Vec<KzgProof>,
+}
+
+impl BlobSidecar {
+  fn build(fill: &BlobFill, count: u32) -> Result<Self> {
+    let settings = ethereum_kzg_settings();
+
+    let mut sidecar = BlobSidecar {
+      blobs: vec![],
+      commitments: vec![],
+      proofs: vec![],
+    };
+    for _ in 0..count {
+      let blob = fill.blob()?;
+      let commitment = KzgCommitment::blob_to_kzg_commitment(&blob, settings)
+        .map_err(|e| anyhow::anyhow!("{:?}", e))
+        .with_context(|| "Failed to compute kzg commitment".to_string())?;
+      let proof = KzgProof::compute_blob_kzg_proof(&blob, &commitment.to_bytes(), settings)
+        .map_err(|e| anyhow::anyhow!("{:?}", e))
+        .with_context(|| "Failed to compute kzg proof".to_string())?;
+
+      sidecar.blobs.push(blob);
+      sidecar.commitments.push(commitment);
+      sidecar.proofs.push(proof);
+    }
+
+    Ok(sidecar)
+  }
+
+  pub fn versioned_hashes(&self) -> Vec<H256> {
+    self
+      .commitments
+      .iter()
+      .map(|commitment| kzg_to_versioned_hash(commitment.as_slice()))
+      .collect()
+  }
+}
+
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> H256 {
+  let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+  hash[0] = VERSIONED_HASH_VERSION_KZG;
+  hash.into()
+}
+
+// eip-4844 transaction, ethers' TypedTransaction doesn't know about type 3 so it's rlp encoded here
+pub struct BlobTransaction {
+  pub chain_id: u64,
+  pub nonce: U256,
+  pub max_priority_fee_per_gas: U256,
+  pub max_fee_per_gas: U256,
+  pub gas: U256,
+  pub to: Address,
+  pub value: U256,
+  pub data: Bytes,
+  pub max_fee_per_blob_gas: U256,
+  pub blob_versioned_hashes: Vec<H256>,
+}
+
+impl BlobTransaction {
+  fn append_fields(&self, stream: &mut RlpStream) {
+    stream.append(&self.chain_id);
+    stream.append(&self.nonce);
+    stream.append(&self.max_priority_fee_per_gas);
+    stream.append(&self.max_fee_per_gas);
+    stream.append(&self.gas);
+    stream.append(&self.to);
+    stream.append(&self.value);
+    stream.append(&self.data.as_ref());
+    // empty access list
+    stream.begin_list(0);
+    stream.append(&self.max_fee_per_blob_gas);
+    stream.append_list(&self.blob_versioned_hashes);
+  }
+
+  pub fn sighash(&self) -> H256 {
+    let mut stream = RlpStream::new_list(11);
+    self.append_fields(&mut stream);
+
+    let mut payload = vec![BLOB_TX_TYPE];
+    payload.extend_from_slice(&stream.out());
+    keccak256(payload).into()
+  }
+
+  fn append_signed(&self, stream: &mut RlpStream, signature: &Signature) {
+    stream.begin_list(14);
+    self.append_fields(stream);
+    // wallet signatures carry the legacy 27/28 recovery id
+    stream.append(&(signature.v - 27));
+    stream.append(&signature.r);
+    stream.append(&signature.s);
+  }
+
+  // the network form sent through `eth_sendRawTransaction`:
+  // 0x03 || rlp([tx_payload_body, blobs, commitments, proofs])
+  pub fn rlp_network(&self, signature: &Signature, sidecar: &BlobSidecar) -> Bytes {
+    let mut stream = RlpStream::new_list(4);
+    self.append_signed(&mut stream, signature);
+    stream.begin_list(sidecar.blobs.len());
+    for blob in &sidecar.blobs {
+      stream.append(&blob.as_slice());
+    }
+    stream.begin_list(sidecar.commitments.len());
+    for commitment in &sidecar.commitments {
+      stream.append(&commitment.as_slice());
+    }
+    stream.begin_list(sidecar.proofs.len());
+    for proof in &sidecar.proofs {
+      stream.append(&proof.as_slice());
+    }
+
+    let mut raw = vec![BLOB_TX_TYPE];
+    raw.extend_from_slice(&stream.out());
+    raw.into()
+  }
+}
+
+// one blob transaction per account in the range, each with its own freshly generated blobs
+pub async fn build_blob_signatures(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  nonce: i64,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  blob_options: Option<BlobOptions>,
+) -> Result<Vec<String>> {
+  let blob_options = blob_options.unwrap_or_default();
+  let nonce = non_negative("nonce", nonce)?;
+  let value = non_negative("value", value)?;
+
+  let to = to_address
+    .parse::<Address>()
+    .with_context(|| "Failed to parse to address".to_string())?;
+  // remove 0x prefix in data
+  let data = &data[2..];
+  let data = hex::decode(data).with_context(|| "Failed to decode data".to_string())?;
+  let gas_price = gas_price
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas price".to_string())?;
+  let gas_limit = gas_limit
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas limit".to_string())?;
+  let max_fee_per_gas = match blob_options.max_fee_per_gas {
+    Some(x) => x
+      .parse::<U256>()
+      .with_context(|| "Failed to parse max fee per gas".to_string())?,
+    None => gas_price,
+  };
+  let max_priority_fee_per_gas = match blob_options.max_priority_fee_per_gas {
+    Some(x) => x
+      .parse::<U256>()
+      .with_context(|| "Failed to parse max priority fee per gas".to_string())?,
+    None => max_fee_per_gas,
+  };
+  let max_fee_per_blob_gas = match blob_options.max_fee_per_blob_gas {
+    Some(x) => x
+      .parse::<U256>()
+      .with_context(|| "Failed to parse max fee per blob gas".to_string())?,
+    None => gas_price,
+  };
+
+  let blobs_per_tx = blob_options.blobs_per_tx.unwrap_or(1);
+  if !(1..=MAX_BLOBS_PER_TX).contains(&blobs_per_tx) {
+    bail!(
+      "blobs_per_tx should be between 1 and {}, got {}",
+      MAX_BLOBS_PER_TX,
+      blobs_per_tx
+    );
+  }
+  let fill = BlobFill::from_option(blob_options.fill)?;
+
+  let range = AccountRange::new(start_index, end_index)?;
+  let signers = accounts.signers(chain_id as u64, range).await?;
+  let mut signed_tx = vec![];
+  for signer in signers {
+    let sidecar = BlobSidecar::build(&fill, blobs_per_tx)?;
+
+    let tx = BlobTransaction {
+      chain_id: chain_id as u64,
+      nonce,
+      max_priority_fee_per_gas,
+      max_fee_per_gas,
+      gas: gas_limit,
+      to,
+      value,
+      data: data.clone().into(),
+      max_fee_per_blob_gas,
+      blob_versioned_hashes: sidecar.versioned_hashes(),
+    };
+
+    let signature = signer
+      .sign_hash(tx.sighash())
+      .with_context(|| "Failed to sign transaction".to_string())?;
+
+    signed_tx.push(tx.rlp_network(&signature, &sidecar).to_string());
+  }
+
+  anyhow::Ok(signed_tx)
+}
+
+#[cfg(test)]
+mod tests {
+  use c_kzg::{ethereum_kzg_settings, Blob, Bytes48, KzgProof, BYTES_PER_BLOB};
+  use ethers::prelude::*;
+  use ethers::utils::keccak256;
+  use ethers::utils::rlp::Rlp;
+
+  use crate::sign::blob::{build_blob_signatures, kzg_to_versioned_hash, BlobOptions};
+  use crate::sign::signer::get_child_signer;
+
+  #[tokio::test]
+  async fn test_build_blob_signatures() {
+    let mnemonic = "test test test test test test test test test test test junk";
+
+    let signed = build_blob_signatures(
+      mnemonic.to_string().into(),
+      1,
+      1,
+      31337,
+      "0x".to_string(),
+      4,
+      0,
+      "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+      "1000000000".to_string(),
+      "21000".to_string(),
+      Some(BlobOptions {
+        blobs_per_tx: Some(2),
+        fill: Some("0xabcd".to_string()),
+        ..Default::default()
+      }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(signed.len(), 1);
+
+    let raw = hex::decode(&signed[0][2..]).unwrap();
+    assert_eq!(raw[0], 0x03);
+
+    let wrapper = Rlp::new(&raw[1..]);
+    let payload = wrapper.at(0).unwrap();
+    assert_eq!(payload.item_count().unwrap(), 14);
+    assert_eq!(payload.val_at::<u64>(0).unwrap(), 31337);
+    assert_eq!(payload.val_at::<U256>(1).unwrap(), 4.into());
+
+    // blobs keep the leading byte of every field element zero
+    let blobs: Vec<Vec<u8>> = wrapper.list_at(1).unwrap();
+    assert_eq!(blobs.len(), 2);
+    assert_eq!(blobs[0].len(), BYTES_PER_BLOB);
+    assert_eq!(&blobs[0][..4], &[0x00, 0xab, 0xcd, 0xab]);
+
+    let commitments: Vec<Vec<u8>> = wrapper.list_at(2).unwrap();
+    let proofs: Vec<Vec<u8>> = wrapper.list_at(3).unwrap();
+    let versioned_hashes: Vec<H256> = payload.list_at(10).unwrap();
+    for i in 0..2 {
+      assert_eq!(versioned_hashes[i], kzg_to_versioned_hash(&commitments[i]));
+      assert!(KzgProof::verify_blob_kzg_proof(
+        &Blob::from_bytes(&blobs[i]).unwrap(),
+        &Bytes48::from_bytes(&commitments[i]).unwrap(),
+        &Bytes48::from_bytes(&proofs[i]).unwrap(),
+        ethereum_kzg_settings(),
+      )
+      .unwrap());
+    }
+
+    // signature over 0x03 || rlp(unsigned fields)
+    let mut unsigned = ethers::utils::rlp::RlpStream::new_list(11);
+    for i in 0..11 {
+      unsigned.append_raw(payload.at(i).unwrap().as_raw(), 1);
+    }
+    let mut sighash = vec![0x03];
+    sighash.extend_from_slice(&unsigned.out());
+    let signature = Signature {
+      r: payload.val_at(12).unwrap(),
+      s: payload.val_at(13).unwrap(),
+      v: payload.val_at::<u64>(11).unwrap() + 27,
+    };
+    assert_eq!(
+      signature.recover(H256::from(keccak256(sighash))).unwrap(),
+      get_child_signer(mnemonic, 1, 31337).address()
+    );
+
+    // negative js numbers are rejected instead of panicking
+    for (nonce, value) in [(-1, 0), (0, -1)] {
+      assert!(build_blob_signatures(
+        mnemonic.to_string().into(),
+        1,
+        1,
+        31337,
+        "0x".to_string(),
+        nonce,
+        value,
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+        "1000000000".to_string(),
+        "21000".to_string(),
+        None,
+      )
+      .await
+      .is_err());
+    }
+  }
+}
@@ -0,0 +1,386 @@
+use anyhow::{bail, Context, Result};
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{AbiParser, Function, ParamType, Token};
+use ethers::core::rand::rngs::StdRng;
+use ethers::core::rand::{Rng, SeedableRng};
+use ethers::prelude::*;
+use serde::Deserialize;
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Deserialize)]
+pub struct CalldataArg {
+  // "account_index", "account_address", "random_address", "random_amount", "counter" or "constant"
+  pub kind: String,
+  // constant only, in the same format as the abi parameter, e.g. "0x..." for addresses
+  pub value: Option<String>,
+  // random_amount only, decimal and inclusive, default to 0 and 2^64 - 1 (or the uint max if
+  // smaller)
+  pub min: Option<String>,
+  pub max: Option<String>,
+  // counter only, decimal, default to 0 and 1
+  pub start: Option<String>,
+  pub step: Option<String>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+#[derive(Clone)]
+pub struct CalldataTemplate {
+  // function signature, e.g. "transfer(address,uint256)" or "function transfer(address to, uint256 amount)"
+  pub signature: String,
+  // one generator per function parameter
+  pub args: Vec<CalldataArg>,
+  // seeds the random generators for reproducible calldata, random by default
+  pub seed: Option<i64>,
+}
+
+enum ArgGenerator {
+  AccountIndex,
+  AccountAddress,
+  RandomAddress,
+  RandomAmount { min: U256, span: Option<U256> },
+  // `max` is the largest value of the uint parameter
+  Counter { next: U256, step: U256, max: U256 },
+  Constant(Token),
+}
+
+impl ArgGenerator {
+  fn from_arg(arg: &CalldataArg, kind: &ParamType) -> Result<Self> {
+    let parse_dec = |name: &str, value: &Option<String>, default: U256| -> Result<U256> {
+      match value {
+        Some(value) => {
+          U256::from_dec_str(value).with_context(|| format!("Failed to parse {}: {}", name, value))
+        }
+        None => Ok(default),
+      }
+    };
+
+    // values past the uint width would be rejected by the contract, or truncated by its decoder
+    let uint_max = match kind {
+      ParamType::Uint(bits) if *bits < 256 => (U256::one() << *bits) - 1,
+      _ => U256::MAX,
+    };
+
+    let generator = match arg.kind.as_str() {
+      "account_index" => ArgGenerator::AccountIndex,
+      "account_address" => ArgGenerator::AccountAddress,
+      "random_address" => ArgGenerator::RandomAddress,
+      "random_amount" => {
+        let min = parse_dec("min", &arg.min, U256::zero())?;
+        let max = parse_dec("max", &arg.max, U256::from(u64::MAX).min(uint_max))?;
+        if min > max {
+          bail!("min {} is greater than max {}", min, max);
+        }
+        if max > uint_max {
+          bail!("max {} doesn't fit in a {}", max, kind);
+        }
+        // none when the range covers the whole u256 space
+        let span = (max - min).checked_add(U256::one());
+        ArgGenerator::RandomAmount { min, span }
+      }
+      "counter" => {
+        let start = parse_dec("start", &arg.start, U256::zero())?;
+        if start > uint_max {
+          bail!("start {} doesn't fit in a {}", start, kind);
+        }
+        ArgGenerator::Counter {
+          next: start,
+          step: parse_dec("step", &arg.step, U256::one())?,
+          max: uint_max,
+        }
+      }
+      "constant" => {
+        let value = arg
+          .value
+          .as_deref()
+          .with_context(|| "constant arg requires a value".to_string())?;
+        ArgGenerator::Constant(
+          LenientTokenizer::tokenize(kind, value)
+            .with_context(|| format!("Failed to tokenize {} as {}", value, kind))?,
+        )
+      }
+      other => bail!("unsupported calldata arg kind: {}", other),
+    };
+
+    let compatible = match generator {
+      ArgGenerator::AccountAddress | ArgGenerator::RandomAddress => {
+        matches!(kind, ParamType::Address)
+      }
+      ArgGenerator::AccountIndex
+      | ArgGenerator::RandomAmount { .. }
+      | ArgGenerator::Counter { .. } => matches!(kind, ParamType::Uint(_)),
+      ArgGenerator::Constant(_) => true,
+    };
+    if !compatible {
+      bail!("{} arg can't encode a {} parameter", arg.kind, kind);
+    }
+
+    Ok(generator)
+  }
+
+  fn next(&mut self, account_index: i64, account: Address, rng: &mut impl Rng) -> Token {
+    match self {
+      ArgGenerator::AccountIndex => Token::Uint(account_index.into()),
+      ArgGenerator::AccountAddress => Token::Address(account),
+      ArgGenerator::RandomAddress => Token::Address(Address::from(rng.gen::<[u8; 20]>())),
+      ArgGenerator::RandomAmount { min, span } => {
+        let random = U256::from_big_endian(&rng.gen::<[u8; 32]>());
+        Token::Uint(match span {
+          Some(span) => *min + random % *span,
+          None => random,
+        })
+      }
+      ArgGenerator::Counter { next, step, .. } => {
+        let value = *next;
+        *next = next.overflowing_add(*step).0;
+        Token::Uint(value)
+      }
+      ArgGenerator::Constant(token) => token.clone(),
+    }
+  }
+}
+
+// calldata built from a function signature and per-parameter generators, evaluated once per
+// signed transaction so accounts don't all send identical payloads
+pub struct Calldata {
+  function: Function,
+  args: Vec<ArgGenerator>,
+}
+
+impl Calldata {
+  #[cfg_attr(not(feature = "napi"), allow(dead_code))]
+  pub fn from_template(template: &CalldataTemplate) -> Result<Self> {
+    let function = AbiParser::default()
+      .parse_function(&template.signature)
+      .with_context(|| format!("Failed to parse function signature: {}", template.signature))?;
+
+    Self::new(function, &template.args)
+  }
+
+  #[cfg_attr(not(feature = "napi"), allow(dead_code))]
+  pub fn rng(template: Option<&CalldataTemplate>) -> StdRng {
+    match template.and_then(|template| template.seed) {
+      Some(seed) => StdRng::seed_from_u64(seed as u64),
+      None => StdRng::from_entropy(),
+    }
+  }
+
+  pub fn new(function: Function, args: &[CalldataArg]) -> Result<Self> {
+    if function.inputs.len() != args.len() {
+      bail!(
+        "{} expects {} args, got {}",
+        function.name,
+        function.inputs.len(),
+        args.len()
+      );
+    }
+
+    let args = function
+      .inputs
+      .iter()
+      .zip(args)
+      .map(|(param, arg)| {
+        ArgGenerator::from_arg(arg, &param.kind)
+          .with_context(|| format!("Invalid arg `{}` of {}", param.name, function.name))
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    Ok(Calldata { function, args })
+  }
+
+  // counters move once per encoding, so `count` encodings have to keep the last value within
+  // its uint type
+  pub fn check_count(&self, count: usize) -> Result<()> {
+    for (arg, param) in self.args.iter().zip(&self.function.inputs) {
+      if let ArgGenerator::Counter { next, step, max } = arg {
+        let last = step
+          .checked_mul(U256::from(count.saturating_sub(1)))
+          .and_then(|increase| increase.checked_add(*next));
+        if last.is_none_or(|last| last > *max) {
+          bail!(
+            "counter `{}` of {} overflows a {} within {} transactions",
+            param.name,
+            self.function.name,
+            param.kind,
+            count
+          );
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn next_tokens(
+    &mut self,
+    account_index: i64,
+    account: Address,
+    rng: &mut impl Rng,
+  ) -> Vec<Token> {
+    self
+      .args
+      .iter_mut()
+      .map(|arg| arg.next(account_index, account, rng))
+      .collect()
+  }
+
+  pub fn encode(
+    &mut self,
+    account_index: i64,
+    account: Address,
+    rng: &mut impl Rng,
+  ) -> Result<Vec<u8>> {
+    let tokens = self.next_tokens(account_index, account, rng);
+
+    self
+      .function
+      .encode_input(&tokens)
+      .with_context(|| format!("Failed to encode calldata of {}", self.function.name))
+  }
+
+  // the abi encoded args without the function selector, e.g. for constructor args
+  #[cfg_attr(not(feature = "napi"), allow(dead_code))]
+  pub fn encode_args(
+    &mut self,
+    account_index: i64,
+    account: Address,
+    rng: &mut impl Rng,
+  ) -> Vec<u8> {
+    let tokens = self.next_tokens(account_index, account, rng);
+
+    ethers::abi::encode(&tokens)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::abi::{AbiParser, Token};
+  use ethers::core::rand::thread_rng;
+  use ethers::prelude::*;
+
+  use crate::sign::calldata::{Calldata, CalldataArg, CalldataTemplate};
+
+  fn arg(kind: &str) -> CalldataArg {
+    CalldataArg {
+      kind: kind.to_string(),
+      value: None,
+      min: None,
+      max: None,
+      start: None,
+      step: None,
+    }
+  }
+
+  #[test]
+  fn test_calldata_template() {
+    let template = CalldataTemplate {
+      signature: "transfer(address,uint256,uint256,address,uint256)".to_string(),
+      args: vec![
+        arg("random_address"),
+        CalldataArg {
+          min: Some("10".to_string()),
+          max: Some("20".to_string()),
+          ..arg("random_amount")
+        },
+        CalldataArg {
+          start: Some("100".to_string()),
+          step: Some("2".to_string()),
+          ..arg("counter")
+        },
+        arg("account_address"),
+        arg("account_index"),
+      ],
+      seed: None,
+    };
+    let function = AbiParser::default()
+      .parse_function(&template.signature)
+      .unwrap();
+    let mut calldata = Calldata::from_template(&template).unwrap();
+
+    let account = Address::repeat_byte(0x11);
+    let mut rng = thread_rng();
+    let first = calldata.encode(7, account, &mut rng).unwrap();
+    let second = calldata.encode(8, account, &mut rng).unwrap();
+
+    assert_eq!(&first[..4], &function.short_signature());
+    let first = function.decode_input(&first[4..]).unwrap();
+    let second = function.decode_input(&second[4..]).unwrap();
+
+    assert_ne!(first[0], second[0]);
+    let amount = first[1].clone().into_uint().unwrap();
+    assert!(amount >= 10.into() && amount <= 20.into());
+    assert_eq!(first[2], Token::Uint(100.into()));
+    assert_eq!(second[2], Token::Uint(102.into()));
+    assert_eq!(first[3], Token::Address(account));
+    assert_eq!(first[4], Token::Uint(7.into()));
+    assert_eq!(second[4], Token::Uint(8.into()));
+  }
+
+  #[test]
+  fn test_calldata_template_validation() {
+    let template = |signature: &str, args: Vec<CalldataArg>| CalldataTemplate {
+      signature: signature.to_string(),
+      args,
+      seed: None,
+    };
+
+    // arity mismatch
+    assert!(Calldata::from_template(&template("transfer(address,uint256)", vec![])).is_err());
+    // generator type mismatch
+    assert!(
+      Calldata::from_template(&template("approve(uint256)", vec![arg("random_address")])).is_err()
+    );
+    // constants are tokenized by the parameter type
+    let mut calldata = Calldata::from_template(&template(
+      "mint(bool)",
+      vec![CalldataArg {
+        value: Some("true".to_string()),
+        ..arg("constant")
+      }],
+    ))
+    .unwrap();
+    let encoded = calldata
+      .encode(0, Address::zero(), &mut thread_rng())
+      .unwrap();
+    assert_eq!(encoded[35], 1);
+  }
+
+  #[test]
+  fn test_calldata_template_uint_bounds() {
+    let template = |arg: CalldataArg| CalldataTemplate {
+      signature: "set(uint8)".to_string(),
+      args: vec![arg],
+      seed: None,
+    };
+
+    // the default max is clamped to the uint width, an explicit one has to fit
+    let mut calldata = Calldata::from_template(&template(arg("random_amount"))).unwrap();
+    for _ in 0..32 {
+      let encoded = calldata
+        .encode(0, Address::zero(), &mut thread_rng())
+        .unwrap();
+      assert!(encoded[4..35].iter().all(|&byte| byte == 0));
+    }
+    assert!(Calldata::from_template(&template(CalldataArg {
+      max: Some("256".to_string()),
+      ..arg("random_amount")
+    }))
+    .is_err());
+
+    // counters have to stay within the uint over every transaction
+    assert!(Calldata::from_template(&template(CalldataArg {
+      start: Some("256".to_string()),
+      ..arg("counter")
+    }))
+    .is_err());
+    let calldata = Calldata::from_template(&template(CalldataArg {
+      start: Some("200".to_string()),
+      step: Some("5".to_string()),
+      ..arg("counter")
+    }))
+    .unwrap();
+    // 200, 205, ..., 255
+    assert!(calldata.check_count(12).is_ok());
+    assert!(calldata.check_count(13).is_err());
+    assert!(calldata.check_count(0).is_ok());
+  }
+}
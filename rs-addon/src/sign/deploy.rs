@@ -0,0 +1,301 @@
+use anyhow::{bail, Context, Result};
+use ethers::prelude::*;
+use ethers::utils::{get_contract_address, get_create2_address_from_hash, keccak256};
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::calldata::{Calldata, CalldataTemplate};
+use crate::sign::signature::{non_negative, sign_typed_transaction, TxEnvelope, TxOptions};
+use crate::sign::signer::SignerSource;
+
+// the deterministic deployment proxy, deployed at the same address on most chains. It creates
+// `calldata[32..]` with CREATE2 using `calldata[..32]` as the salt
+pub const DEFAULT_CREATE2_FACTORY: &str = "0x4e59b44847b379578588920cA78FbF26c0B4956C";
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct DeployOptions {
+  // deploy through a CREATE2 factory instead of a contract creation transaction
+  pub create2: Option<bool>,
+  // CREATE2 only, defaults to the deterministic deployment proxy
+  pub factory_address: Option<String>,
+  // CREATE2 only, 32 bytes hex, defaults to zero. The account index is added to it so every
+  // account deploys to its own address
+  pub salt: Option<String>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+pub struct DeploySignatures {
+  pub signed_txs: Vec<String>,
+  // predicted address of the contract deployed by the transaction at the same position
+  pub addresses: Vec<String>,
+}
+
+enum DeployMethod {
+  Create,
+  Create2 { factory: Address, salt: U256 },
+}
+
+impl DeployMethod {
+  fn from_options(options: DeployOptions) -> Result<Self> {
+    if !options.create2.unwrap_or(false) {
+      if options.factory_address.is_some() || options.salt.is_some() {
+        bail!("factory address and salt are only used with create2");
+      }
+      return Ok(DeployMethod::Create);
+    }
+
+    let factory = options
+      .factory_address
+      .as_deref()
+      .unwrap_or(DEFAULT_CREATE2_FACTORY)
+      .parse::<Address>()
+      .with_context(|| "Failed to parse factory address".to_string())?;
+    let salt = options
+      .salt
+      .map(|salt| salt.parse::<H256>())
+      .transpose()
+      .with_context(|| "Failed to parse salt".to_string())?
+      .unwrap_or_default();
+
+    Ok(DeployMethod::Create2 {
+      factory,
+      salt: U256::from_big_endian(salt.as_bytes()),
+    })
+  }
+}
+
+pub fn create2_address(factory: Address, salt: H256, init_code: &[u8]) -> Address {
+  get_create2_address_from_hash(factory, salt, keccak256(init_code))
+}
+
+// one deployment per account in the range. `constructor` encodes the constructor args appended
+// to `bytecode`, its signature only lists the types, e.g. "constructor(string,uint256)"
+pub async fn build_deploy_signatures(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  bytecode: String,
+  constructor: Option<CalldataTemplate>,
+  nonce: i64,
+  value: i64,
+  gas_price: String,
+  gas_limit: String,
+  deploy_options: Option<DeployOptions>,
+  tx_options: Option<TxOptions>,
+) -> Result<DeploySignatures> {
+  let nonce = non_negative("nonce", nonce)?;
+  let value = non_negative("value", value)?;
+  let method = DeployMethod::from_options(deploy_options.unwrap_or_default())?;
+  let envelope = TxEnvelope::from_options(tx_options)?;
+  let mut rng = Calldata::rng(constructor.as_ref());
+  let mut constructor = constructor
+    .as_ref()
+    .map(Calldata::from_template)
+    .transpose()
+    .with_context(|| "Invalid constructor template".to_string())?;
+  let bytecode = hex::decode(bytecode.trim_start_matches("0x"))
+    .with_context(|| "Failed to decode bytecode".to_string())?;
+  let gas_price = gas_price
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas price".to_string())?;
+  let gas_limit = gas_limit
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas limit".to_string())?;
+
+  let range = AccountRange::new(start_index, end_index)?;
+  if let Some(constructor) = &constructor {
+    constructor.check_count(range.len())?;
+  }
+  let signers = accounts.signers(chain_id as u64, range).await?;
+  let mut deployments = DeploySignatures {
+    signed_txs: Vec::with_capacity(signers.len()),
+    addresses: Vec::with_capacity(signers.len()),
+  };
+  for (signer, index) in signers.iter().zip(start_index..) {
+    let mut init_code = bytecode.clone();
+    if let Some(constructor) = constructor.as_mut() {
+      init_code.extend(constructor.encode_args(index, signer.address(), &mut rng));
+    }
+
+    let tx = TransactionRequest::new()
+      .value(value)
+      .nonce(nonce)
+      .gas_price(gas_price)
+      .gas(gas_limit);
+    let (tx, address) = match &method {
+      DeployMethod::Create => (
+        tx.data(init_code),
+        get_contract_address(signer.address(), nonce),
+      ),
+      DeployMethod::Create2 { factory, salt } => {
+        let mut salt_bytes = [0u8; 32];
+        salt
+          .overflowing_add(index.into())
+          .0
+          .to_big_endian(&mut salt_bytes);
+        let address = create2_address(*factory, salt_bytes.into(), &init_code);
+        (
+          tx.to(*factory).data([&salt_bytes[..], &init_code].concat()),
+          address,
+        )
+      }
+    };
+
+    deployments
+      .signed_txs
+      .push(sign_typed_transaction(signer, envelope.wrap(tx)).await?);
+    deployments.addresses.push(format!("{:?}", address));
+  }
+
+  anyhow::Ok(deployments)
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::abi::{decode, ParamType, Token};
+  use ethers::prelude::*;
+  use ethers::types::transaction::eip2718::TypedTransaction;
+  use ethers::utils::rlp::Rlp;
+
+  use crate::sign::calldata::{CalldataArg, CalldataTemplate};
+  use crate::sign::deploy::{
+    build_deploy_signatures, create2_address, DeployOptions, DEFAULT_CREATE2_FACTORY,
+  };
+
+  const MNEMONIC: &str = "test test test test test test test test test test test junk";
+  const BYTECODE: &str = "0x6080604052";
+
+  fn decode_tx(raw: &str) -> TypedTransaction {
+    let raw = hex::decode(&raw[2..]).unwrap();
+    TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap().0
+  }
+
+  #[test]
+  fn test_create2_address() {
+    // example 1 of eip-1014
+    assert_eq!(
+      create2_address(Address::zero(), H256::zero(), &[0x00]),
+      "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+        .parse::<Address>()
+        .unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_build_create_signatures() {
+    let constructor = CalldataTemplate {
+      signature: "constructor(uint256)".to_string(),
+      args: vec![CalldataArg {
+        kind: "account_index".to_string(),
+        value: None,
+        min: None,
+        max: None,
+        start: None,
+        step: None,
+      }],
+      seed: None,
+    };
+
+    let deployments = build_deploy_signatures(
+      MNEMONIC.to_string().into(),
+      0,
+      0,
+      31337,
+      BYTECODE.to_string(),
+      Some(constructor),
+      0,
+      0,
+      "0x3b9aca00".to_string(),
+      "0x2dc6c0".to_string(),
+      None,
+      None,
+    )
+    .await
+    .unwrap();
+
+    // first deployment of the first hardhat account
+    assert_eq!(
+      deployments.addresses,
+      vec!["0x5fbdb2315678afecb367f032d93f642f64180aa3"]
+    );
+
+    let tx = decode_tx(&deployments.signed_txs[0]);
+    assert_eq!(tx.to(), None);
+    let data = tx.data().unwrap();
+    assert_eq!(&data[..5], &hex::decode(&BYTECODE[2..]).unwrap()[..]);
+    assert_eq!(
+      decode(&[ParamType::Uint(256)], &data[5..]).unwrap(),
+      vec![Token::Uint(0.into())]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_build_create2_signatures() {
+    let deployments = build_deploy_signatures(
+      MNEMONIC.to_string().into(),
+      1,
+      2,
+      31337,
+      BYTECODE.to_string(),
+      None,
+      0,
+      0,
+      "0x3b9aca00".to_string(),
+      "0x2dc6c0".to_string(),
+      Some(DeployOptions {
+        create2: Some(true),
+        ..Default::default()
+      }),
+      None,
+    )
+    .await
+    .unwrap();
+
+    let factory = DEFAULT_CREATE2_FACTORY.parse::<Address>().unwrap();
+    let init_code = hex::decode(&BYTECODE[2..]).unwrap();
+    for (i, (raw, address)) in deployments
+      .signed_txs
+      .iter()
+      .zip(&deployments.addresses)
+      .enumerate()
+    {
+      let tx = decode_tx(raw);
+      assert_eq!(tx.to_addr(), Some(&factory));
+
+      // the account index is the salt
+      let salt = H256::from_low_u64_be(i as u64 + 1);
+      let data = tx.data().unwrap();
+      assert_eq!(&data[..32], salt.as_bytes());
+      assert_eq!(&data[32..], &init_code[..]);
+      assert_eq!(
+        address,
+        &format!("{:?}", create2_address(factory, salt, &init_code))
+      );
+    }
+    assert_ne!(deployments.addresses[0], deployments.addresses[1]);
+  }
+
+  #[tokio::test]
+  async fn test_negative_numbers() {
+    let build = |nonce: i64, value: i64| {
+      build_deploy_signatures(
+        MNEMONIC.to_string().into(),
+        0,
+        0,
+        31337,
+        BYTECODE.to_string(),
+        None,
+        nonce,
+        value,
+        "0x3b9aca00".to_string(),
+        "0x2dc6c0".to_string(),
+        None,
+        None,
+      )
+    };
+    assert!(build(-1, 0).await.is_err());
+    assert!(build(0, -1).await.is_err());
+    assert!(build(0, 0).await.is_ok());
+  }
+}
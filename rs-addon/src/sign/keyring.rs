@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use anyhow::{bail, Context, Result};
+use coins_bip32::ecdsa::SigningKey;
+use ethers::core::rand::{thread_rng, RngCore};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+
+const MAGIC: &[u8; 4] = b"RSKR";
+const VERSION: u8 = 1;
+// magic, version, scrypt log_n, r and p, salt, nonce
+const HEADER_LEN: usize = 4 + 1 + 1 + 4 + 4 + 32 + 12;
+// a keyring entry: account index and secret key
+const ENTRY_LEN: usize = 4 + 32;
+// refuse files asking for more than 16 GiB of scrypt memory
+const MAX_KDF_LOG_N: u8 = 24;
+
+// scrypt cost of a saved keyring, the defaults take about a second and 64 MiB
+#[derive(Clone, Copy)]
+pub struct KdfParams {
+  pub log_n: u8,
+  pub r: u32,
+  pub p: u32,
+}
+
+impl Default for KdfParams {
+  fn default() -> Self {
+    KdfParams {
+      log_n: 16,
+      r: 8,
+      p: 1,
+    }
+  }
+}
+
+impl KdfParams {
+  fn derive_key(&self, password: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    if self.log_n > MAX_KDF_LOG_N {
+      bail!(
+        "scrypt log_n {} is greater than {}",
+        self.log_n,
+        MAX_KDF_LOG_N
+      );
+    }
+    let params = scrypt::Params::new(self.log_n, self.r, self.p)
+      .map_err(|e| anyhow::anyhow!("Invalid scrypt params: {}", e))?;
+
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+      .map_err(|e| anyhow::anyhow!("Failed to derive keyring key: {}", e))?;
+    Ok(key)
+  }
+}
+
+// child keys derived so far, grouped by the tree they were derived from. Trees are identified by a
+// digest of the mnemonic and derivation path, so the mnemonic itself is never kept. Wallets are
+// cached without a chain id, callers set their own.
+// Nothing is ever evicted, every account signed for stays cached (about 200 bytes each, up to
+// 400 MB per million accounts with the map overhead) until `clear`, exposed to node as
+// `rsClearKeyring`
+#[derive(Default)]
+pub struct Keyring {
+  trees: HashMap<H256, HashMap<u32, LocalWallet>>,
+}
+
+static KEYRING: OnceLock<Mutex<Keyring>> = OnceLock::new();
+
+// the process wide keyring shared by every napi call. Don't hold it across an await
+pub fn keyring() -> MutexGuard<'static, Keyring> {
+  KEYRING
+    .get_or_init(Default::default)
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+}
+
+impl Keyring {
+  pub fn tree_id(mnemonic: &str, derive_path: &str) -> H256 {
+    keccak256([derive_path.as_bytes(), &[0], mnemonic.as_bytes()].concat()).into()
+  }
+
+  pub fn get(&self, tree: H256, index: u32) -> Option<&LocalWallet> {
+    self.trees.get(&tree)?.get(&index)
+  }
+
+  pub fn insert(&mut self, tree: H256, index: u32, wallet: LocalWallet) {
+    self.trees.entry(tree).or_default().insert(index, wallet);
+  }
+
+  pub fn len(&self) -> usize {
+    self.trees.values().map(HashMap::len).sum()
+  }
+
+  #[cfg_attr(not(feature = "napi"), allow(dead_code))]
+  pub fn clear(&mut self) {
+    self.trees.clear();
+  }
+
+  // tree id, entry count, then (index, secret key) per entry, all big endian
+  fn encode(&self) -> Vec<u8> {
+    let mut plaintext = Vec::with_capacity(self.trees.len() * 36 + self.len() * ENTRY_LEN);
+    for (tree, wallets) in self.trees.iter() {
+      plaintext.extend_from_slice(tree.as_bytes());
+      plaintext.extend_from_slice(&(wallets.len() as u32).to_be_bytes());
+      for (index, wallet) in wallets.iter() {
+        plaintext.extend_from_slice(&index.to_be_bytes());
+        plaintext.extend_from_slice(&wallet.signer().to_bytes());
+      }
+    }
+    plaintext
+  }
+
+  fn decode(mut plaintext: &[u8]) -> Result<Vec<(H256, u32, LocalWallet)>> {
+    let mut entries = vec![];
+    while !plaintext.is_empty() {
+      if plaintext.len() < 36 {
+        bail!("truncated keyring tree");
+      }
+      let tree = H256::from_slice(&plaintext[..32]);
+      let count = u32::from_be_bytes(plaintext[32..36].try_into()?) as usize;
+      plaintext = &plaintext[36..];
+
+      let len = count
+        .checked_mul(ENTRY_LEN)
+        .filter(|&len| len <= plaintext.len())
+        .with_context(|| "truncated keyring entries".to_string())?;
+      for entry in plaintext[..len].chunks_exact(ENTRY_LEN) {
+        let index = u32::from_be_bytes(entry[..4].try_into()?);
+        let key = SigningKey::from_bytes(entry[4..].into())
+          .with_context(|| format!("Invalid key of account {}", index))?;
+        entries.push((tree, index, Wallet::from(key)));
+      }
+      plaintext = &plaintext[len..];
+    }
+    Ok(entries)
+  }
+
+  // encrypts every cached key with a key derived from `password` and writes them to `path`,
+  // returns the number of keys saved
+  pub fn save(&self, path: &str, password: &str, params: KdfParams) -> Result<usize> {
+    let mut salt = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    thread_rng().fill_bytes(&mut salt);
+    thread_rng().fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    header.push(params.log_n);
+    header.extend_from_slice(&params.r.to_be_bytes());
+    header.extend_from_slice(&params.p.to_be_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let key = params.derive_key(password, &salt)?;
+    // the header is authenticated, so its kdf params can't be swapped
+    let ciphertext = Aes256Gcm::new(&key.into())
+      .encrypt(
+        &Nonce::from(nonce),
+        Payload {
+          msg: &self.encode(),
+          aad: &header,
+        },
+      )
+      .map_err(|_| anyhow::anyhow!("Failed to encrypt keyring"))?;
+
+    write_private(path, &[header, ciphertext].concat())
+      .with_context(|| format!("Failed to write keyring: {}", path))?;
+    Ok(self.len())
+  }
+
+  // decrypts the keys saved at `path` and adds them to the keyring, returns the number of keys
+  // loaded
+  pub fn load(&mut self, path: &str, password: &str) -> Result<usize> {
+    let content =
+      std::fs::read(path).with_context(|| format!("Failed to read keyring: {}", path))?;
+    if content.len() < HEADER_LEN || &content[..4] != MAGIC {
+      bail!("{} is not a keyring file", path);
+    }
+    if content[4] != VERSION {
+      bail!("unsupported keyring version {}", content[4]);
+    }
+
+    let (header, ciphertext) = content.split_at(HEADER_LEN);
+    let params = KdfParams {
+      log_n: header[5],
+      r: u32::from_be_bytes(header[6..10].try_into()?),
+      p: u32::from_be_bytes(header[10..14].try_into()?),
+    };
+    let salt = &header[14..46];
+    let nonce: [u8; 12] = header[46..58].try_into()?;
+
+    let key = params.derive_key(password, salt)?;
+    let plaintext = Aes256Gcm::new(&key.into())
+      .decrypt(
+        &Nonce::from(nonce),
+        Payload {
+          msg: ciphertext,
+          aad: header,
+        },
+      )
+      .map_err(|_| anyhow::anyhow!("Failed to decrypt keyring, wrong password?"))?;
+
+    let entries = Self::decode(&plaintext)?;
+    let count = entries.len();
+    for (tree, index, wallet) in entries {
+      self.insert(tree, index, wallet);
+    }
+    Ok(count)
+  }
+}
+
+// writes to a temporary file readable by the owner only, then renames it over `path`, so an
+// interrupted save leaves the previous keyring intact
+fn write_private(path: &str, content: &[u8]) -> std::io::Result<()> {
+  let temp = format!("{}.{}.tmp", path, std::process::id());
+  let mut options = std::fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+  let result = options.open(&temp).and_then(|mut file| {
+    file.write_all(content)?;
+    file.sync_all()
+  });
+  let result = result.and_then(|_| std::fs::rename(&temp, path));
+  if result.is_err() {
+    let _ = std::fs::remove_file(&temp);
+  }
+  result
+}
+
+pub async fn load_keyring(path: String, password: String) -> Result<usize> {
+  tokio::task::spawn_blocking(move || keyring().load(&path, &password)).await?
+}
+
+pub async fn save_keyring(path: String, password: String) -> Result<usize> {
+  tokio::task::spawn_blocking(move || keyring().save(&path, &password, KdfParams::default()))
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::prelude::*;
+
+  use crate::sign::keyring::{KdfParams, Keyring};
+  use crate::sign::signer::get_child_signer;
+
+  const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+  #[test]
+  fn test_keyring_persist() {
+    let path = std::env::temp_dir().join(format!("rs-addon-keyring-{}", std::process::id()));
+    let path = path.to_string_lossy().to_string();
+    let params = KdfParams {
+      log_n: 4,
+      ..Default::default()
+    };
+
+    let tree = Keyring::tree_id(MNEMONIC, "m/44'/60'/0'/0");
+    let mut keyring = Keyring::default();
+    for index in [0, 7, 1_000_000] {
+      keyring.insert(tree, index, get_child_signer(MNEMONIC, index, 1));
+    }
+    assert_eq!(keyring.save(&path, "secret", params).unwrap(), 3);
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let mut loaded = Keyring::default();
+    assert!(loaded.load(&path, "wrong").is_err());
+    assert_eq!(loaded.load(&path, "secret").unwrap(), 3);
+    for index in [0, 7, 1_000_000] {
+      assert_eq!(
+        loaded.get(tree, index).unwrap().address(),
+        keyring.get(tree, index).unwrap().address()
+      );
+    }
+    assert!(loaded.get(tree, 1).is_none());
+    assert!(loaded.get(H256::zero(), 0).is_none());
+
+    // saving again replaces the file, without leaving the temporary one behind
+    assert_eq!(Keyring::default().save(&path, "secret", params).unwrap(), 0);
+    assert_eq!(Keyring::default().load(&path, "secret").unwrap(), 0);
+    assert!(!std::path::Path::new(&format!("{}.{}.tmp", path, std::process::id())).exists());
+    assert_eq!(keyring.save(&path, "secret", params).unwrap(), 3);
+
+    // tampered kdf params fail authentication
+    let mut content = std::fs::read(&path).unwrap();
+    content[5] = 5;
+    std::fs::write(&path, content).unwrap();
+    assert!(Keyring::default().load(&path, "secret").is_err());
+    std::fs::remove_file(&path).unwrap();
+  }
+}
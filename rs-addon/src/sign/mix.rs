@@ -0,0 +1,261 @@
+use anyhow::{bail, Context, Result};
+use ethers::core::rand::rngs::StdRng;
+use ethers::core::rand::seq::SliceRandom;
+use ethers::core::rand::{Rng, SeedableRng};
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::calldata::{CalldataArg, CalldataTemplate};
+use crate::sign::signature::{build_native_token_transfer_signatures, build_signatures, TxOptions};
+use crate::sign::signer::SignerSource;
+use crate::sign::workload::apportion;
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone)]
+pub struct MixEntry {
+  // "native", "erc20" or "call"
+  pub kind: String,
+  pub weight: u32,
+  // recipient for native transfers, token for erc20 transfers, contract for calls
+  pub to_address: String,
+  // native and call only, defaults to 0
+  pub value: Option<i64>,
+  // call only, fixed calldata used when there's no template
+  pub data: Option<String>,
+  // erc20 defaults to `transfer(address,uint256)` of 1 unit to a random address
+  pub calldata: Option<CalldataTemplate>,
+  // overrides the mix gas limit
+  pub gas_limit: Option<String>,
+}
+
+enum MixKind {
+  Native,
+  Erc20,
+  Call,
+}
+
+impl MixKind {
+  fn from_str(kind: &str) -> Result<Self> {
+    match kind {
+      "native" => Ok(MixKind::Native),
+      "erc20" => Ok(MixKind::Erc20),
+      "call" => Ok(MixKind::Call),
+      other => bail!("unsupported mix kind: {}", other),
+    }
+  }
+}
+
+fn default_erc20_transfer(seed: i64) -> CalldataTemplate {
+  let arg = |kind: &str, value: Option<&str>| CalldataArg {
+    kind: kind.to_string(),
+    value: value.map(str::to_string),
+    min: None,
+    max: None,
+    start: None,
+    step: None,
+  };
+
+  CalldataTemplate {
+    signature: "transfer(address,uint256)".to_string(),
+    args: vec![arg("random_address", None), arg("constant", Some("1"))],
+    seed: Some(seed),
+  }
+}
+
+// positions of every entry's transactions in the mixed output: each entry appears as many times
+// as its count, in an order shuffled by `rng`
+fn interleave(counts: &[usize], rng: &mut StdRng) -> Vec<usize> {
+  let mut order: Vec<usize> = counts
+    .iter()
+    .enumerate()
+    .flat_map(|(entry, &count)| std::iter::repeat_n(entry, count))
+    .collect();
+  order.shuffle(rng);
+  order
+}
+
+// one transaction per account in the range, accounts are handed to the entries in proportion to
+// their weights (consecutive ranges, in entry order) and the signed transactions are interleaved
+// with `seed`, so the same seed always gives the same mix
+pub async fn build_mixed_signatures(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  gas_price: String,
+  gas_limit: String,
+  entries: Vec<MixEntry>,
+  seed: i64,
+  tx_options: Option<TxOptions>,
+) -> Result<Vec<String>> {
+  if entries.is_empty() {
+    bail!("mix should have at least one entry");
+  }
+  let range = AccountRange::new(start_index, end_index)?;
+
+  let weights: Vec<u32> = entries.iter().map(|entry| entry.weight).collect();
+  let counts = apportion(&weights, range.len())?;
+  let mut rng = StdRng::seed_from_u64(seed as u64);
+
+  let mut signed_by_entry = Vec::with_capacity(entries.len());
+  let mut next_index = start_index;
+  for (entry, &count) in entries.iter().zip(&counts) {
+    // drawn for every entry, so an empty entry doesn't shift the seeds of the next ones
+    let entry_seed: i64 = rng.gen();
+    if count == 0 {
+      signed_by_entry.push(vec![].into_iter());
+      continue;
+    }
+
+    let (entry_start, entry_end) = (next_index, next_index + count as i64 - 1);
+    next_index += count as i64;
+    let gas_limit = entry.gas_limit.clone().unwrap_or_else(|| gas_limit.clone());
+
+    let kind = MixKind::from_str(&entry.kind)?;
+    let signed = match kind {
+      MixKind::Native => {
+        build_native_token_transfer_signatures(
+          accounts.clone(),
+          entry.to_address.clone(),
+          chain_id,
+          entry_start,
+          entry_end,
+          entry.value.unwrap_or(0),
+          gas_price.clone(),
+          gas_limit,
+          tx_options.clone(),
+          None,
+        )
+        .await
+      }
+      MixKind::Erc20 | MixKind::Call => {
+        let calldata = match (&entry.calldata, kind) {
+          (Some(calldata), _) => Some(CalldataTemplate {
+            seed: calldata.seed.or(Some(entry_seed)),
+            ..calldata.clone()
+          }),
+          (None, MixKind::Erc20) => Some(default_erc20_transfer(entry_seed)),
+          (None, _) => None,
+        };
+
+        build_signatures(
+          accounts.clone(),
+          entry_start,
+          entry_end,
+          chain_id,
+          entry.data.clone().unwrap_or_else(|| "0x".to_string()),
+          0,
+          entry.value.unwrap_or(0),
+          entry.to_address.clone(),
+          gas_price.clone(),
+          gas_limit,
+          tx_options.clone(),
+          calldata,
+        )
+        .await
+      }
+    }
+    .with_context(|| format!("Failed to build {} transactions", entry.kind))?;
+
+    signed_by_entry.push(signed.into_iter());
+  }
+
+  let signed_tx = interleave(&counts, &mut rng)
+    .into_iter()
+    .map(|entry| signed_by_entry[entry].next().unwrap())
+    .collect();
+
+  anyhow::Ok(signed_tx)
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::core::rand::rngs::StdRng;
+  use ethers::core::rand::SeedableRng;
+  use ethers::prelude::*;
+  use ethers::types::transaction::eip2718::TypedTransaction;
+  use ethers::utils::rlp::Rlp;
+
+  use crate::sign::mix::{build_mixed_signatures, interleave, MixEntry};
+
+  const TOKEN: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+  const RECIPIENT: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+  fn entry(kind: &str, weight: u32, to_address: &str) -> MixEntry {
+    MixEntry {
+      kind: kind.to_string(),
+      weight,
+      to_address: to_address.to_string(),
+      value: None,
+      data: None,
+      calldata: None,
+      gas_limit: None,
+    }
+  }
+
+  #[test]
+  fn test_interleave() {
+    let order = interleave(&[3, 2, 1], &mut StdRng::seed_from_u64(7));
+    assert_eq!(order.len(), 6);
+    for (entry, count) in [(0, 3), (1, 2), (2, 1)] {
+      assert_eq!(order.iter().filter(|&&x| x == entry).count(), count);
+    }
+    assert_eq!(order, interleave(&[3, 2, 1], &mut StdRng::seed_from_u64(7)));
+  }
+
+  #[tokio::test]
+  async fn test_build_mixed_signatures() {
+    let build = |seed: i64| {
+      build_mixed_signatures(
+        "test test test test test test test test test test test junk"
+          .to_string()
+          .into(),
+        4,
+        13,
+        31337,
+        "0x3b9aca00".to_string(),
+        "0x186a0".to_string(),
+        vec![
+          entry("erc20", 3, TOKEN),
+          MixEntry {
+            value: Some(100),
+            ..entry("native", 6, RECIPIENT)
+          },
+          MixEntry {
+            data: Some("0x12345678".to_string()),
+            ..entry("call", 1, TOKEN)
+          },
+        ],
+        seed,
+        None,
+      )
+    };
+
+    let signed = build(42).await.unwrap();
+    assert_eq!(signed, build(42).await.unwrap());
+    assert_ne!(signed, build(43).await.unwrap());
+
+    let txs: Vec<TypedTransaction> = signed
+      .iter()
+      .map(|raw| {
+        let raw = hex::decode(&raw[2..]).unwrap();
+        TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap().0
+      })
+      .collect();
+    assert_eq!(txs.len(), 10);
+
+    let recipient = RECIPIENT.parse::<Address>().unwrap();
+    let native = txs
+      .iter()
+      .filter(|tx| tx.to_addr() == Some(&recipient))
+      .count();
+    let erc20 = txs
+      .iter()
+      .filter(|tx| tx.data().is_some_and(|data| data.len() == 68))
+      .count();
+    let calls = txs
+      .iter()
+      .filter(|tx| tx.data().is_some_and(|data| data.len() == 4))
+      .count();
+    assert_eq!((native, erc20, calls), (6, 3, 1));
+  }
+}
@@ -1,2 +1,18 @@
+pub mod account_range;
+// builders only called from node are unused by the cli
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+pub mod blob;
+pub mod calldata;
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+pub mod deploy;
+pub mod keyring;
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+pub mod mix;
+pub mod partition;
+pub mod presign;
+pub mod round;
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+pub mod set_code;
 pub mod signature;
 pub mod signer;
+pub mod workload;
@@ -0,0 +1,124 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::fund::batch_transfer_contract::BATCH_SIZE;
+use crate::sign::account_range::AccountRange;
+
+// the accounts one of several load generators works with. Benchmark accounts come from the random
+// mnemonic, funder accounts from the funding mnemonic; no two workers share either, so their
+// nonces never collide
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountPartition {
+  pub worker_id: u32,
+  pub worker_count: u32,
+  // benchmark accounts, inclusive
+  pub start_index: i64,
+  pub end_index: i64,
+  // funder accounts of the worker's funding tree, one per batch of BATCH_SIZE benchmark accounts.
+  // The first one is the root: it needs the funds of the whole tree, sends the first batch and
+  // funds the others
+  pub funding_start_index: i64,
+  pub funding_end_index: i64,
+}
+
+impl AccountPartition {
+  pub fn accounts(&self) -> Result<AccountRange> {
+    AccountRange::new(self.start_index, self.end_index)
+  }
+
+  pub fn funders(&self) -> Result<AccountRange> {
+    AccountRange::new(self.funding_start_index, self.funding_end_index)
+  }
+}
+
+fn batch_count(accounts: usize) -> usize {
+  accounts.div_ceil(BATCH_SIZE)
+}
+
+// splits `accounts` into `worker_count` consecutive ranges, the first `len % worker_count` workers
+// taking one more account. Funder accounts are split in blocks sized for the largest share, worker
+// w's tree starting at `w * block`, so a single worker keeps the funding tree rooted at account 0
+pub fn partition_accounts(
+  worker_id: u32,
+  worker_count: u32,
+  accounts: AccountRange,
+) -> Result<AccountPartition> {
+  if worker_count == 0 {
+    bail!("worker count should be at least 1");
+  }
+  if worker_id >= worker_count {
+    bail!("worker id {} is out of {} workers", worker_id, worker_count);
+  }
+  let (len, workers, worker) = (accounts.len(), worker_count as usize, worker_id as usize);
+  if len < workers {
+    bail!("{} accounts can't be split among {} workers", len, workers);
+  }
+
+  let (share, extra) = (len / workers, len % workers);
+  let start = *accounts.indexes().start() as usize + worker * share + worker.min(extra);
+  let worker_len = share + usize::from(worker < extra);
+  let block = batch_count(share + usize::from(extra > 0));
+  let funding_start = worker * block;
+
+  let partition = AccountPartition {
+    worker_id,
+    worker_count,
+    start_index: start as i64,
+    end_index: (start + worker_len - 1) as i64,
+    funding_start_index: funding_start as i64,
+    funding_end_index: (funding_start + batch_count(worker_len) - 1) as i64,
+  };
+  // the funder block of the last worker may overflow the account indexes
+  partition.funders()?;
+
+  Ok(partition)
+}
+
+// the partition funded when there's none: accounts 1 to `total_accounts` on a single worker
+pub fn single_worker(total_accounts: i64) -> Result<AccountPartition> {
+  partition_accounts(0, 1, AccountRange::new(1, total_accounts)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::sign::account_range::AccountRange;
+  use crate::sign::partition::{partition_accounts, single_worker};
+
+  #[test]
+  fn test_partition_accounts() {
+    // a single worker keeps the historical layout
+    let single = single_worker(500).unwrap();
+    assert_eq!((single.start_index, single.end_index), (1, 500));
+    assert_eq!(
+      (single.funding_start_index, single.funding_end_index),
+      (0, 2)
+    );
+
+    let range = AccountRange::new(10, 1010).unwrap();
+    let partitions: Vec<_> = (0..3)
+      .map(|worker| partition_accounts(worker, 3, range).unwrap())
+      .collect();
+    let ranges: Vec<_> = partitions
+      .iter()
+      .map(|p| {
+        (
+          p.start_index,
+          p.end_index,
+          p.funding_start_index,
+          p.funding_end_index,
+        )
+      })
+      .collect();
+    // 1001 accounts: 334 + 334 + 333, 2 batches each
+    assert_eq!(
+      ranges,
+      vec![(10, 343, 0, 1), (344, 677, 2, 3), (678, 1010, 4, 5)]
+    );
+    assert_eq!(partitions[1], partition_accounts(1, 3, range).unwrap());
+
+    assert!(partition_accounts(3, 3, range).is_err());
+    assert!(partition_accounts(0, 0, range).is_err());
+    assert!(partition_accounts(0, 4, AccountRange::new(1, 3).unwrap()).is_err());
+  }
+}
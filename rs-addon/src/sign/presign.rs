@@ -0,0 +1,313 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::round::RoundSpec;
+
+const MAGIC: &[u8; 4] = b"RSTX";
+const VERSION: u8 = 1;
+// round, account index and raw transaction length
+const RECORD_HEADER_LEN: usize = 4 + 4 + 4;
+
+// signed transactions are saved either as json lines, `{"round":0,"account":1,"tx":"0x..."}`, or
+// in a binary file: magic, version, then per transaction the round, account index and length of
+// the raw transaction, all big endian, and the raw transaction itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresignFormat {
+  Jsonl,
+  Binary,
+}
+
+impl PresignFormat {
+  // `jsonl` or `binary`, told from the extension by default: `.jsonl` files are json lines
+  pub fn new(format: Option<&str>, path: &str) -> Result<Self> {
+    match format {
+      Some("jsonl") => Ok(PresignFormat::Jsonl),
+      Some("binary") => Ok(PresignFormat::Binary),
+      Some(format) => bail!("unknown presign format {}", format),
+      None if Path::new(path).extension().is_some_and(|e| e == "jsonl") => Ok(PresignFormat::Jsonl),
+      None => Ok(PresignFormat::Binary),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRecord {
+  round: u32,
+  account: u32,
+  tx: String,
+}
+
+pub struct PresignWriter {
+  format: PresignFormat,
+  writer: BufWriter<File>,
+  count: usize,
+}
+
+impl PresignWriter {
+  pub fn create(path: &str, format: PresignFormat) -> Result<Self> {
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+    let mut writer = BufWriter::new(file);
+    if format == PresignFormat::Binary {
+      writer.write_all(MAGIC)?;
+      writer.write_all(&[VERSION])?;
+    }
+    Ok(PresignWriter {
+      format,
+      writer,
+      count: 0,
+    })
+  }
+
+  // `transactions` are the signed transactions of `accounts`, in order
+  pub fn write_round(
+    &mut self,
+    round: u32,
+    accounts: AccountRange,
+    transactions: &[String],
+  ) -> Result<()> {
+    if transactions.len() != accounts.len() {
+      bail!(
+        "round {} has {} transactions for {} accounts",
+        round,
+        transactions.len(),
+        accounts.len()
+      );
+    }
+
+    for (account, tx) in accounts.indexes().zip(transactions) {
+      match self.format {
+        PresignFormat::Jsonl => {
+          let record = JsonRecord {
+            round,
+            account,
+            tx: tx.clone(),
+          };
+          serde_json::to_writer(&mut self.writer, &record)?;
+          self.writer.write_all(b"\n")?;
+        }
+        PresignFormat::Binary => {
+          let raw = hex::decode(tx.trim_start_matches("0x"))
+            .with_context(|| format!("Invalid transaction of account {}", account))?;
+          self.writer.write_all(&round.to_be_bytes())?;
+          self.writer.write_all(&account.to_be_bytes())?;
+          self.writer.write_all(&(raw.len() as u32).to_be_bytes())?;
+          self.writer.write_all(&raw)?;
+        }
+      }
+    }
+    self.count += transactions.len();
+    Ok(())
+  }
+
+  // returns the number of transactions written
+  pub fn finish(mut self) -> Result<usize> {
+    self.writer.flush()?;
+    Ok(self.count)
+  }
+}
+
+// appends `(round, tx)` to `rounds`, rounds have to come in order from 0
+fn push_record(rounds: &mut Vec<Vec<String>>, round: u32, tx: String) -> Result<()> {
+  let round = round as usize;
+  if round == rounds.len() {
+    rounds.push(vec![]);
+  } else if round + 1 != rounds.len() {
+    bail!(
+      "round {} is out of order, rounds should follow each other from 0",
+      round
+    );
+  }
+  rounds[round].push(tx);
+  Ok(())
+}
+
+// the transactions saved at `path`, grouped by round
+pub fn read_presigned(path: &str, format: PresignFormat) -> Result<Vec<Vec<String>>> {
+  let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+  let mut reader = BufReader::new(file);
+  let mut rounds = vec![];
+
+  match format {
+    PresignFormat::Jsonl => {
+      for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+          continue;
+        }
+        let record: JsonRecord = serde_json::from_str(&line)
+          .with_context(|| format!("Invalid transaction at line {}", i + 1))?;
+        push_record(&mut rounds, record.round, record.tx)?;
+      }
+    }
+    PresignFormat::Binary => {
+      let mut content = vec![];
+      reader.read_to_end(&mut content)?;
+      if content.len() < 5 || &content[..4] != MAGIC {
+        bail!("{} is not a presigned transaction file", path);
+      }
+      if content[4] != VERSION {
+        bail!("unsupported presigned file version {}", content[4]);
+      }
+
+      let mut rest = &content[5..];
+      while !rest.is_empty() {
+        if rest.len() < RECORD_HEADER_LEN {
+          bail!("truncated presigned transaction");
+        }
+        let round = u32::from_be_bytes(rest[..4].try_into()?);
+        let len = u32::from_be_bytes(rest[8..12].try_into()?) as usize;
+        rest = &rest[RECORD_HEADER_LEN..];
+        if rest.len() < len {
+          bail!("truncated presigned transaction");
+        }
+        push_record(
+          &mut rounds,
+          round,
+          format!("0x{}", hex::encode(&rest[..len])),
+        )?;
+        rest = &rest[len..];
+      }
+    }
+  }
+
+  Ok(rounds)
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+pub struct PresignOptions {
+  pub path: String,
+  // `jsonl` or `binary`, told from the path by default
+  pub format: Option<String>,
+  pub transactions: RoundSpec,
+  // first account of the first round, the next rounds take the accounts that follow
+  pub start_index: i64,
+  // transactions per round
+  pub rounds: Vec<u32>,
+}
+
+// signs every round of a benchmark to a file, so sending it later isn't slowed down by signing.
+// Returns the number of transactions written
+pub async fn presign_transactions(options: PresignOptions) -> Result<usize> {
+  let format = PresignFormat::new(options.format.as_deref(), &options.path)?;
+  let mut writer = PresignWriter::create(&options.path, format)?;
+
+  let mut start_index = options.start_index;
+  for (round, &size) in options.rounds.iter().enumerate() {
+    let accounts = AccountRange::with_len(start_index, size as i64)?;
+    let transactions = options.transactions.build(accounts).await?;
+    writer.write_round(round as u32, accounts, &transactions)?;
+    log::info!(
+      "round {} signed | Account Index {} - {}",
+      round,
+      accounts.indexes().start(),
+      accounts.indexes().end()
+    );
+    start_index += size as i64;
+  }
+
+  writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::sign::presign::{presign_transactions, read_presigned, PresignFormat, PresignOptions};
+  use crate::sign::round::RoundSpec;
+  use crate::sign::signature::build_native_token_transfer_signatures;
+
+  const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+  #[test]
+  fn test_presign_format() {
+    assert_eq!(
+      PresignFormat::new(None, "txs.jsonl").unwrap(),
+      PresignFormat::Jsonl
+    );
+    assert_eq!(
+      PresignFormat::new(None, "txs.bin").unwrap(),
+      PresignFormat::Binary
+    );
+    assert_eq!(
+      PresignFormat::new(Some("jsonl"), "txs.bin").unwrap(),
+      PresignFormat::Jsonl
+    );
+    assert!(PresignFormat::new(Some("csv"), "txs.csv").is_err());
+  }
+
+  #[tokio::test]
+  async fn test_presign_transactions() {
+    let spec = RoundSpec {
+      accounts: MNEMONIC.to_string().into(),
+      chain_id: 1,
+      gas_price: "0x3b9aca00".to_string(),
+      gas_limit: "0x5208".to_string(),
+      to_address: Some("0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string()),
+      value: Some(100),
+      workload_path: None,
+      tx_options: None,
+    };
+    let expected = build_native_token_transfer_signatures(
+      MNEMONIC.to_string().into(),
+      "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+      1,
+      3,
+      8,
+      100,
+      "0x3b9aca00".to_string(),
+      "0x5208".to_string(),
+      None,
+      None,
+    )
+    .await
+    .unwrap();
+
+    for extension in ["jsonl", "bin"] {
+      let path = std::env::temp_dir().join(format!(
+        "rs-addon-presign-{}.{}",
+        std::process::id(),
+        extension
+      ));
+      let path = path.to_string_lossy().to_string();
+
+      let count = presign_transactions(PresignOptions {
+        path: path.clone(),
+        format: None,
+        transactions: spec.clone(),
+        start_index: 3,
+        rounds: vec![2, 4],
+      })
+      .await
+      .unwrap();
+      assert_eq!(count, 6);
+
+      let format = PresignFormat::new(None, &path).unwrap();
+      let rounds = read_presigned(&path, format).unwrap();
+      assert_eq!(rounds, vec![expected[..2].to_vec(), expected[2..].to_vec()]);
+      std::fs::remove_file(&path).unwrap();
+    }
+  }
+
+  #[test]
+  fn test_read_presigned_out_of_order() {
+    let path = std::env::temp_dir().join(format!("rs-addon-presign-{}-order", std::process::id()));
+    let path = path.to_string_lossy().to_string();
+    std::fs::write(
+      &path,
+      "{\"round\":0,\"account\":1,\"tx\":\"0x01\"}\n{\"round\":2,\"account\":2,\"tx\":\"0x02\"}\n",
+    )
+    .unwrap();
+    assert!(read_presigned(&path, PresignFormat::Jsonl).is_err());
+
+    std::fs::write(
+      &path,
+      b"RSTX\x01\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x05\x01",
+    )
+    .unwrap();
+    assert!(read_presigned(&path, PresignFormat::Binary).is_err());
+    std::fs::remove_file(&path).unwrap();
+  }
+}
@@ -0,0 +1,61 @@
+use anyhow::{bail, Result};
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::signature::{build_native_token_transfer_signatures, TxOptions};
+use crate::sign::signer::SignerSource;
+use crate::sign::workload::build_workload_signatures;
+
+// the transactions of a benchmark round, one per account: native token transfers to `to_address`
+// unless a workload file is given
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone)]
+pub struct RoundSpec {
+  pub accounts: SignerSource,
+  pub chain_id: i64,
+  pub gas_price: String,
+  pub gas_limit: String,
+  pub to_address: Option<String>,
+  pub value: Option<i64>,
+  pub workload_path: Option<String>,
+  pub tx_options: Option<TxOptions>,
+}
+
+impl RoundSpec {
+  pub async fn build(&self, accounts: AccountRange) -> Result<Vec<String>> {
+    let indexes = accounts.indexes();
+    let (start_index, end_index) = (*indexes.start() as i64, *indexes.end() as i64);
+
+    if let Some(workload_path) = &self.workload_path {
+      return build_workload_signatures(
+        self.accounts.clone(),
+        start_index,
+        end_index,
+        self.chain_id,
+        0,
+        self.gas_price.clone(),
+        self.gas_limit.clone(),
+        workload_path.clone(),
+        self.tx_options.clone(),
+        None,
+      )
+      .await;
+    }
+
+    let Some(to_address) = &self.to_address else {
+      bail!("round needs either a to address or a workload path");
+    };
+    build_native_token_transfer_signatures(
+      self.accounts.clone(),
+      to_address.clone(),
+      self.chain_id,
+      start_index,
+      end_index,
+      self.value.unwrap_or_default(),
+      self.gas_price.clone(),
+      self.gas_limit.clone(),
+      self.tx_options.clone(),
+      None,
+    )
+    .await
+  }
+}
@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use ethers::utils::rlp::RlpStream;
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::signature::non_negative;
+use crate::sign::signer::SignerSource;
+
+pub const SET_CODE_TX_TYPE: u8 = 0x04;
+// prefix of the authorization signing payload, keeps it apart from transaction signatures
+const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct SetCodeOptions {
+  // chain the authorizations are valid on, 0 for any chain, defaults to the tx chain id
+  pub authorization_chain_id: Option<i64>,
+  // defaults to the tx nonce + 1, as the sender is also the authority its nonce is bumped first
+  pub authorization_nonce: Option<i64>,
+  // defaults to gas price
+  pub max_fee_per_gas: Option<String>,
+  // defaults to max fee per gas
+  pub max_priority_fee_per_gas: Option<String>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+pub struct AuthorizationTuple {
+  pub chain_id: String,
+  pub address: String,
+  pub nonce: i64,
+  pub y_parity: u32,
+  pub r: String,
+  pub s: String,
+}
+
+// eip-7702 authorization, delegating the code of the signing account to `address`
+#[derive(Clone, Debug)]
+pub struct Authorization {
+  pub chain_id: U256,
+  pub address: Address,
+  pub nonce: u64,
+}
+
+impl Authorization {
+  fn append_fields(&self, stream: &mut RlpStream) {
+    stream.append(&self.chain_id);
+    stream.append(&self.address);
+    stream.append(&self.nonce);
+  }
+
+  // keccak256(0x05 || rlp([chain_id, address, nonce]))
+  pub fn signature_hash(&self) -> H256 {
+    let mut stream = RlpStream::new_list(3);
+    self.append_fields(&mut stream);
+
+    let mut payload = vec![AUTHORIZATION_MAGIC];
+    payload.extend_from_slice(&stream.out());
+    keccak256(payload).into()
+  }
+
+  pub fn sign(self, signer: &LocalWallet) -> Result<SignedAuthorization> {
+    let signature = signer
+      .sign_hash(self.signature_hash())
+      .with_context(|| "Failed to sign authorization".to_string())?;
+
+    Ok(SignedAuthorization {
+      authorization: self,
+      signature,
+    })
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct SignedAuthorization {
+  pub authorization: Authorization,
+  pub signature: Signature,
+}
+
+impl SignedAuthorization {
+  // wallet signatures carry the legacy 27/28 recovery id
+  pub fn y_parity(&self) -> u64 {
+    self.signature.v - 27
+  }
+
+  fn append(&self, stream: &mut RlpStream) {
+    stream.begin_list(6);
+    self.authorization.append_fields(stream);
+    stream.append(&self.y_parity());
+    stream.append(&self.signature.r);
+    stream.append(&self.signature.s);
+  }
+}
+
+impl From<SignedAuthorization> for AuthorizationTuple {
+  fn from(signed: SignedAuthorization) -> Self {
+    AuthorizationTuple {
+      chain_id: signed.authorization.chain_id.to_string(),
+      address: format!("{:?}", signed.authorization.address),
+      nonce: signed.authorization.nonce as i64,
+      y_parity: signed.y_parity() as u32,
+      r: format!("0x{:064x}", signed.signature.r),
+      s: format!("0x{:064x}", signed.signature.s),
+    }
+  }
+}
+
+// eip-7702 transaction, ethers' TypedTransaction doesn't know about type 4 so it's rlp encoded here
+pub struct SetCodeTransaction {
+  pub chain_id: u64,
+  pub nonce: U256,
+  pub max_priority_fee_per_gas: U256,
+  pub max_fee_per_gas: U256,
+  pub gas: U256,
+  pub to: Address,
+  pub value: U256,
+  pub data: Bytes,
+  pub authorization_list: Vec<SignedAuthorization>,
+}
+
+impl SetCodeTransaction {
+  fn append_fields(&self, stream: &mut RlpStream) {
+    stream.append(&self.chain_id);
+    stream.append(&self.nonce);
+    stream.append(&self.max_priority_fee_per_gas);
+    stream.append(&self.max_fee_per_gas);
+    stream.append(&self.gas);
+    stream.append(&self.to);
+    stream.append(&self.value);
+    stream.append(&self.data.as_ref());
+    // empty access list
+    stream.begin_list(0);
+    stream.begin_list(self.authorization_list.len());
+    for authorization in &self.authorization_list {
+      authorization.append(stream);
+    }
+  }
+
+  pub fn sighash(&self) -> H256 {
+    let mut stream = RlpStream::new_list(10);
+    self.append_fields(&mut stream);
+
+    let mut payload = vec![SET_CODE_TX_TYPE];
+    payload.extend_from_slice(&stream.out());
+    keccak256(payload).into()
+  }
+
+  pub fn rlp_signed(&self, signature: &Signature) -> Bytes {
+    let mut stream = RlpStream::new_list(13);
+    self.append_fields(&mut stream);
+    stream.append(&(signature.v - 27));
+    stream.append(&signature.r);
+    stream.append(&signature.s);
+
+    let mut raw = vec![SET_CODE_TX_TYPE];
+    raw.extend_from_slice(&stream.out());
+    raw.into()
+  }
+}
+
+// authorizations from every account in the range delegating to `delegate_address`, to be
+// included by any sender
+pub async fn build_authorizations(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  delegate_address: String,
+  nonce: i64,
+) -> Result<Vec<AuthorizationTuple>> {
+  let delegate = delegate_address
+    .parse::<Address>()
+    .with_context(|| "Failed to parse delegate address".to_string())?;
+  let authorization_chain_id = non_negative("chain id", chain_id)?;
+  let nonce = non_negative("nonce", nonce)?.as_u64();
+
+  let range = AccountRange::new(start_index, end_index)?;
+  let signers = accounts.signers(chain_id as u64, range).await?;
+  signers
+    .iter()
+    .map(|signer| {
+      let authorization = Authorization {
+        chain_id: authorization_chain_id,
+        address: delegate,
+        nonce,
+      };
+      anyhow::Ok(authorization.sign(signer)?.into())
+    })
+    .collect()
+}
+
+// one type-4 transaction per account in the range, each carrying the sender's own authorization
+// delegating its code to `delegate_address`
+pub async fn build_set_code_signatures(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  nonce: i64,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  delegate_address: String,
+  set_code_options: Option<SetCodeOptions>,
+) -> Result<Vec<String>> {
+  let set_code_options = set_code_options.unwrap_or_default();
+  let nonce = non_negative("nonce", nonce)?;
+  let value = non_negative("value", value)?;
+
+  let to = to_address
+    .parse::<Address>()
+    .with_context(|| "Failed to parse to address".to_string())?;
+  let delegate = delegate_address
+    .parse::<Address>()
+    .with_context(|| "Failed to parse delegate address".to_string())?;
+  // remove 0x prefix in data
+  let data = &data[2..];
+  let data = hex::decode(data).with_context(|| "Failed to decode data".to_string())?;
+  let gas_price = gas_price
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas price".to_string())?;
+  let gas_limit = gas_limit
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas limit".to_string())?;
+  let max_fee_per_gas = match set_code_options.max_fee_per_gas {
+    Some(x) => x
+      .parse::<U256>()
+      .with_context(|| "Failed to parse max fee per gas".to_string())?,
+    None => gas_price,
+  };
+  let max_priority_fee_per_gas = match set_code_options.max_priority_fee_per_gas {
+    Some(x) => x
+      .parse::<U256>()
+      .with_context(|| "Failed to parse max priority fee per gas".to_string())?,
+    None => max_fee_per_gas,
+  };
+  let authorization_chain_id = non_negative(
+    "authorization chain id",
+    set_code_options.authorization_chain_id.unwrap_or(chain_id),
+  )?;
+  let authorization_nonce = match set_code_options.authorization_nonce {
+    Some(authorization_nonce) => non_negative("authorization nonce", authorization_nonce)?,
+    None => nonce + 1,
+  }
+  .as_u64();
+
+  let range = AccountRange::new(start_index, end_index)?;
+  let signers = accounts.signers(chain_id as u64, range).await?;
+  let mut signed_tx = vec![];
+  for signer in signers {
+    let authorization = Authorization {
+      chain_id: authorization_chain_id,
+      address: delegate,
+      nonce: authorization_nonce,
+    }
+    .sign(&signer)?;
+
+    let tx = SetCodeTransaction {
+      chain_id: chain_id as u64,
+      nonce,
+      max_priority_fee_per_gas,
+      max_fee_per_gas,
+      gas: gas_limit,
+      to,
+      value,
+      data: data.clone().into(),
+      authorization_list: vec![authorization],
+    };
+
+    let signature = signer
+      .sign_hash(tx.sighash())
+      .with_context(|| "Failed to sign transaction".to_string())?;
+
+    signed_tx.push(tx.rlp_signed(&signature).to_string());
+  }
+
+  anyhow::Ok(signed_tx)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::sign::set_code::{build_authorizations, build_set_code_signatures, SetCodeOptions};
+
+  const MNEMONIC: &str = "test test test test test test test test test test test junk";
+  const DELEGATE: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+
+  // the expected values are printed by `python3 scripts/eip7702_vectors.py`, a standard library
+  // only implementation written from the specs, signed by account 1 of the test mnemonic
+  #[tokio::test]
+  async fn test_build_authorizations() {
+    let authorizations = build_authorizations(
+      MNEMONIC.to_string().into(),
+      1,
+      1,
+      31337,
+      DELEGATE.to_string(),
+      1,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(authorizations.len(), 1);
+    assert_eq!(authorizations[0].chain_id, "31337");
+    assert_eq!(
+      authorizations[0].address,
+      "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+    );
+    assert_eq!(authorizations[0].nonce, 1);
+    assert_eq!(authorizations[0].y_parity, 1);
+    assert_eq!(
+      authorizations[0].r,
+      "0x3272ab13448e05b1fbdab68d1fd6b2b96f4c5fe59a0e9c91e76d0b07d00092d3"
+    );
+    assert_eq!(
+      authorizations[0].s,
+      "0x21fdc8db045cd29f5ff7b2a8e54966eb5dad90eab8b61fe79c511d164d1dbce5"
+    );
+  }
+
+  // same vectors as `test_build_authorizations`
+  #[tokio::test]
+  async fn test_build_set_code_signatures() {
+    let signed = build_set_code_signatures(
+      MNEMONIC.to_string().into(),
+      1,
+      1,
+      31337,
+      "0x1234".to_string(),
+      0,
+      0,
+      "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+      "0x77359400".to_string(),
+      "0x186a0".to_string(),
+      DELEGATE.to_string(),
+      Some(SetCodeOptions {
+        max_priority_fee_per_gas: Some("0x3b9aca00".to_string()),
+        ..Default::default()
+      }),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+      signed,
+      vec!["0x04f8cf827a6980843b9aca008477359400830186a09470997970c51812dc3a010c7d01b50e0d17dc79c880821234c0f85ef85c827a69945fbdb2315678afecb367f032d93f642f64180aa30101a03272ab13448e05b1fbdab68d1fd6b2b96f4c5fe59a0e9c91e76d0b07d00092d3a021fdc8db045cd29f5ff7b2a8e54966eb5dad90eab8b61fe79c511d164d1dbce501a0b14753de6836a2fe07e2b346cb1618fb0213a9df37aad139be1932e43825b87ca04986d86c4a06c2db1b25c80157739988537af2bc825dfccb9934b1b90588e1da"]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_negative_numbers() {
+    assert!(build_authorizations(
+      MNEMONIC.to_string().into(),
+      1,
+      1,
+      31337,
+      DELEGATE.to_string(),
+      -1,
+    )
+    .await
+    .is_err());
+
+    let build = |nonce: i64, value: i64, options: SetCodeOptions| {
+      build_set_code_signatures(
+        MNEMONIC.to_string().into(),
+        1,
+        1,
+        31337,
+        "0x".to_string(),
+        nonce,
+        value,
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+        "0x77359400".to_string(),
+        "0x186a0".to_string(),
+        DELEGATE.to_string(),
+        Some(options),
+      )
+    };
+    assert!(build(-1, 0, SetCodeOptions::default()).await.is_err());
+    assert!(build(0, -1, SetCodeOptions::default()).await.is_err());
+    assert!(build(
+      0,
+      0,
+      SetCodeOptions {
+        authorization_nonce: Some(-1),
+        ..Default::default()
+      }
+    )
+    .await
+    .is_err());
+    assert!(build(
+      0,
+      0,
+      SetCodeOptions {
+        authorization_chain_id: Some(-1),
+        ..Default::default()
+      }
+    )
+    .await
+    .is_err());
+  }
+}
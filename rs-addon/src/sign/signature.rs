@@ -1,11 +1,150 @@
 use ethers::prelude::*;
 
-use crate::sign::signer::get_child_signers_with_range;
-use anyhow::{Context, Result};
+use crate::provider::{connect_provider, ConnectionOptions};
+use crate::sign::account_range::AccountRange;
+use crate::sign::calldata::{Calldata, CalldataTemplate};
+use crate::sign::signer::SignerSource;
+use anyhow::{bail, Context, Result};
 use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
 
+// concurrent `eth_getTransactionCount` requests when fetching start nonces
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+const MAX_NONCE_REQUESTS: usize = 64;
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone)]
+pub struct AccessListEntry {
+  pub address: String,
+  pub storage_keys: Vec<String>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct TxOptions {
+  // 0 legacy (default), 1 eip-2930, 2 eip-1559
+  pub tx_type: Option<u32>,
+  // eip-1559 only, defaults to gas price
+  pub max_fee_per_gas: Option<String>,
+  // eip-1559 only, defaults to max fee per gas
+  pub max_priority_fee_per_gas: Option<String>,
+  // eip-2930 and eip-1559 only
+  pub access_list: Option<Vec<AccessListEntry>>,
+}
+
+// turns the legacy-style request every builder assembles into the configured envelope type
+pub struct TxEnvelope {
+  tx_type: u32,
+  max_fee_per_gas: Option<U256>,
+  max_priority_fee_per_gas: Option<U256>,
+  access_list: AccessList,
+}
+
+impl TxEnvelope {
+  pub fn from_options(options: Option<TxOptions>) -> Result<Self> {
+    let options = options.unwrap_or_default();
+
+    let tx_type = options.tx_type.unwrap_or(0);
+    if tx_type > 2 {
+      bail!("unsupported tx type: {}", tx_type);
+    }
+
+    let max_fee_per_gas = options
+      .max_fee_per_gas
+      .map(|x| x.parse::<U256>())
+      .transpose()
+      .with_context(|| "Failed to parse max fee per gas".to_string())?;
+    let max_priority_fee_per_gas = options
+      .max_priority_fee_per_gas
+      .map(|x| x.parse::<U256>())
+      .transpose()
+      .with_context(|| "Failed to parse max priority fee per gas".to_string())?;
+
+    let access_list = options
+      .access_list
+      .unwrap_or_default()
+      .into_iter()
+      .map(|entry| {
+        let address = entry
+          .address
+          .parse::<Address>()
+          .with_context(|| "Failed to parse access list address".to_string())?;
+        let storage_keys = entry
+          .storage_keys
+          .iter()
+          .map(|key| key.parse::<H256>())
+          .collect::<std::result::Result<Vec<_>, _>>()
+          .with_context(|| "Failed to parse access list storage key".to_string())?;
+        anyhow::Ok(AccessListItem {
+          address,
+          storage_keys,
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    anyhow::Ok(TxEnvelope {
+      tx_type,
+      max_fee_per_gas,
+      max_priority_fee_per_gas,
+      access_list: access_list.into(),
+    })
+  }
+
+  pub fn wrap(&self, tx: TransactionRequest) -> TypedTransaction {
+    match self.tx_type {
+      1 => TypedTransaction::Eip2930(tx.with_access_list(self.access_list.clone())),
+      2 => {
+        let max_fee_per_gas = self.max_fee_per_gas.or(tx.gas_price);
+        let max_priority_fee_per_gas = self.max_priority_fee_per_gas.or(max_fee_per_gas);
+        TypedTransaction::Eip1559(Eip1559TransactionRequest {
+          from: tx.from,
+          to: tx.to,
+          gas: tx.gas,
+          value: tx.value,
+          data: tx.data,
+          nonce: tx.nonce,
+          access_list: self.access_list.clone(),
+          max_priority_fee_per_gas,
+          max_fee_per_gas,
+          chain_id: tx.chain_id,
+        })
+      }
+      _ => TypedTransaction::Legacy(tx),
+    }
+  }
+}
+
+// nonces and values come from js as i64, a negative one would panic or wrap converting to u256
+pub fn non_negative(name: &str, value: i64) -> Result<U256> {
+  if value < 0 {
+    bail!("{} should not be negative, got {}", name, value);
+  }
+  anyhow::Ok(U256::from(value))
+}
+
+pub async fn sign_typed_transaction(
+  signer: &LocalWallet,
+  typed_tx: TypedTransaction,
+) -> Result<String> {
+  // legacy txs carry the chain id in `v`, typed envelopes encode it as a field so it must be set
+  // on the tx itself, the wallet only fills it in for the signing hash
+  let mut typed_tx = typed_tx;
+  if typed_tx.chain_id().is_none() {
+    typed_tx.set_chain_id(signer.chain_id());
+  }
+
+  let signature = signer
+    .sign_transaction(&typed_tx)
+    .await
+    .with_context(|| "Failed to sign transaction".to_string())?;
+
+  anyhow::Ok(typed_tx.rlp_signed(&signature).to_string())
+}
+
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
 pub async fn build_signatures(
-  mnemonic: String,
+  accounts: SignerSource,
   start_index: i64,
   end_index: i64,
   chain_id: i64,
@@ -15,9 +154,23 @@ pub async fn build_signatures(
   to_address: String,
   gas_price: String,
   gas_limit: String,
+  tx_options: Option<TxOptions>,
+  calldata: Option<CalldataTemplate>,
 ) -> Result<Vec<String>> {
-  log::info!("random generated mnemonic: {}", mnemonic);
+  if let Some(mnemonic) = &accounts.mnemonic {
+    log::info!("random generated mnemonic: {}", mnemonic);
+  }
 
+  let envelope = TxEnvelope::from_options(tx_options)?;
+  let nonce = non_negative("nonce", nonce)?;
+  let value = non_negative("value", value)?;
+  // when given, the template replaces `data` with calldata built per signer
+  let mut rng = Calldata::rng(calldata.as_ref());
+  let mut calldata = calldata
+    .as_ref()
+    .map(Calldata::from_template)
+    .transpose()
+    .with_context(|| "Invalid calldata template".to_string())?;
   let to = to_address
     .parse::<Address>()
     .with_context(|| "Failed to parse to address".to_string())?;
@@ -31,13 +184,17 @@ pub async fn build_signatures(
     .parse::<U256>()
     .with_context(|| "Failed to parse gas limit".to_string())?;
 
-  let signers =
-    get_child_signers_with_range(&mnemonic, chain_id as u64, start_index, end_index).await;
+  let range = AccountRange::new(start_index, end_index)?;
+  if let Some(calldata) = &calldata {
+    calldata.check_count(range.len())?;
+  }
+  let signers = accounts.signers(chain_id as u64, range).await?;
   let mut signed_tx = vec![];
-  for i in start_index..=end_index {
-    let data = data.clone();
-    let index = i as usize - start_index as usize;
-    let signer = signers[index].clone();
+  for (signer, i) in signers.iter().zip(range.indexes()) {
+    let data = match calldata.as_mut() {
+      Some(calldata) => calldata.encode(i as i64, signer.address(), &mut rng)?,
+      None => data.clone(),
+    };
 
     let tx = TransactionRequest::new()
       .to(to)
@@ -47,23 +204,16 @@ pub async fn build_signatures(
       .gas_price(gas_price)
       .gas(gas_limit);
 
-    let typed_tx = TypedTransaction::Legacy(tx.clone());
-
-    let signature = signer
-      .sign_transaction(&typed_tx)
-      .await
-      .with_context(|| "Failed to sign transaction".to_string())?;
+    let typed_tx = envelope.wrap(tx);
 
-    let rlp_signed = typed_tx.rlp_signed(&signature);
-
-    signed_tx.push(rlp_signed.to_string());
+    signed_tx.push(sign_typed_transaction(signer, typed_tx).await?);
   }
 
   anyhow::Ok(signed_tx)
 }
 
 pub async fn build_native_token_transfer_signatures(
-  mnemonic: String,
+  accounts: SignerSource,
   to_address: String,
   chain_id: i64,
   start_index: i64,
@@ -71,9 +221,17 @@ pub async fn build_native_token_transfer_signatures(
   value: i64,
   gas_price: String,
   gas_limit: String,
+  tx_options: Option<TxOptions>,
+  // 0 by default, for accounts that haven't sent anything yet
+  nonce: Option<i64>,
 ) -> Result<Vec<String>> {
-  log::info!("random generated mnemonic: {}", mnemonic);
+  if let Some(mnemonic) = &accounts.mnemonic {
+    log::info!("random generated mnemonic: {}", mnemonic);
+  }
 
+  let envelope = TxEnvelope::from_options(tx_options)?;
+  let nonce = non_negative("nonce", nonce.unwrap_or(0))?;
+  let value = non_negative("value", value)?;
   let to = to_address
     .parse::<Address>()
     .with_context(|| "Failed to parse to address".to_string())?;
@@ -84,30 +242,317 @@ pub async fn build_native_token_transfer_signatures(
     .parse::<U256>()
     .with_context(|| "Failed to parse gas limit".to_string())?;
 
-  let signers =
-    get_child_signers_with_range(&mnemonic, chain_id as u64, start_index, end_index).await;
+  let range = AccountRange::new(start_index, end_index)?;
+  let signers = accounts.signers(chain_id as u64, range).await?;
   let mut signed_tx = vec![];
 
-  for i in start_index..=end_index {
-    let signer = signers[i as usize - 1].clone();
+  for signer in signers.iter() {
     let tx = TransactionRequest::new()
       .to(to)
       .value(value)
-      .nonce(0)
+      .nonce(nonce)
       .gas_price(gas_price)
       .gas(gas_limit);
 
-    let typed_tx = TypedTransaction::Legacy(tx.clone());
+    let typed_tx = envelope.wrap(tx);
+
+    signed_tx.push(sign_typed_transaction(signer, typed_tx).await?);
+  }
 
-    let signature = signer
-      .sign_transaction(&typed_tx)
+  anyhow::Ok(signed_tx)
+}
+
+// current pending nonce of every signer, at most MAX_NONCE_REQUESTS requests in flight so large
+// ranges don't flood the node
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+async fn fetch_pending_nonces(http_provider: &str, signers: &[LocalWallet]) -> Result<Vec<U256>> {
+  let provider = connect_provider(http_provider, &ConnectionOptions::default())
+    .await
+    .with_context(|| "Failed to create provider".to_string())?;
+
+  let addresses: Vec<Address> = signers.iter().map(|signer| signer.address()).collect();
+  stream::iter(addresses)
+    .map(|address| {
+      let provider = provider.clone();
+      async move {
+        provider
+          .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+          .await
+          .with_context(|| format!("Failed to get nonce of {:?}", address))
+      }
+    })
+    .buffered(MAX_NONCE_REQUESTS)
+    .try_collect()
+    .await
+}
+
+// signs `txs_per_account` sequential-nonce transactions for every account in the range, ordered
+// nonce by nonce (all accounts' first tx, then all accounts' second tx, ...) so they can be sent
+// in order. Starting nonces are either supplied per account or fetched from `http_provider`.
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+pub async fn build_sequential_signatures(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  data: String,
+  value: i64,
+  to_address: String,
+  gas_price: String,
+  gas_limit: String,
+  txs_per_account: i64,
+  start_nonces: Option<Vec<i64>>,
+  http_provider: Option<String>,
+  tx_options: Option<TxOptions>,
+) -> Result<Vec<String>> {
+  let envelope = TxEnvelope::from_options(tx_options)?;
+  let value = non_negative("value", value)?;
+  let to = to_address
+    .parse::<Address>()
+    .with_context(|| "Failed to parse to address".to_string())?;
+  // remove 0x prefix in data
+  let data = &data[2..];
+  let data = hex::decode(data).with_context(|| "Failed to decode data".to_string())?;
+  let gas_price = gas_price
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas price".to_string())?;
+  let gas_limit = gas_limit
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas limit".to_string())?;
+  if txs_per_account < 1 {
+    bail!(
+      "txs_per_account should be at least 1, got {}",
+      txs_per_account
+    );
+  }
+
+  let range = AccountRange::new(start_index, end_index)?;
+  let signers = accounts.signers(chain_id as u64, range).await?;
+
+  let start_nonces = match (start_nonces, http_provider) {
+    (Some(start_nonces), _) => {
+      if start_nonces.len() != signers.len() {
+        bail!(
+          "expected {} start nonces, got {}",
+          signers.len(),
+          start_nonces.len()
+        );
+      }
+      start_nonces
+        .into_iter()
+        .map(|nonce| non_negative("start nonce", nonce))
+        .collect::<Result<_>>()?
+    }
+    (None, Some(http_provider)) => fetch_pending_nonces(&http_provider, &signers)
       .await
-      .with_context(|| "Failed to sign transaction".to_string())?;
+      .with_context(|| "Failed to fetch start nonces".to_string())?,
+    (None, None) => bail!("either start nonces or a provider to fetch them is required"),
+  };
+
+  let mut signed_tx = Vec::with_capacity(signers.len() * txs_per_account as usize);
+  for n in 0..txs_per_account {
+    for (signer, start_nonce) in signers.iter().zip(&start_nonces) {
+      let tx = TransactionRequest::new()
+        .to(to)
+        .value(value)
+        .data(data.clone())
+        .nonce(start_nonce + n)
+        .gas_price(gas_price)
+        .gas(gas_limit);
 
-    let rlp_signed = typed_tx.rlp_signed(&signature);
+      let typed_tx = envelope.wrap(tx);
 
-    signed_tx.push(rlp_signed.to_string());
+      signed_tx.push(sign_typed_transaction(signer, typed_tx).await?);
+    }
   }
 
   anyhow::Ok(signed_tx)
 }
+
+#[cfg(test)]
+mod tests {
+  use ethers::prelude::*;
+  use ethers::types::transaction::eip2718::TypedTransaction;
+  use ethers::utils::rlp::Rlp;
+
+  use crate::sign::signature::{
+    build_native_token_transfer_signatures, build_sequential_signatures, build_signatures,
+    AccessListEntry, TxOptions,
+  };
+  use crate::sign::signer::get_child_signer;
+
+  #[tokio::test]
+  async fn test_build_sequential_signatures() {
+    let mnemonic = "test test test test test test test test test test test junk";
+
+    let signed = build_sequential_signatures(
+      mnemonic.to_string().into(),
+      1,
+      2,
+      31337,
+      "0x".to_string(),
+      1,
+      "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+      "1000000000".to_string(),
+      "21000".to_string(),
+      2,
+      Some(vec![5, 0]),
+      None,
+      None,
+    )
+    .await
+    .unwrap();
+
+    let senders_and_nonces: Vec<(Address, U256)> = signed
+      .iter()
+      .map(|raw| {
+        let raw = hex::decode(&raw[2..]).unwrap();
+        let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        (
+          signature.recover(tx.sighash()).unwrap(),
+          *tx.nonce().unwrap(),
+        )
+      })
+      .collect();
+
+    let account1 = get_child_signer(mnemonic, 1, 31337).address();
+    let account2 = get_child_signer(mnemonic, 2, 31337).address();
+    assert_eq!(
+      senders_and_nonces,
+      vec![
+        (account1, 5.into()),
+        (account2, 0.into()),
+        (account1, 6.into()),
+        (account2, 1.into()),
+      ]
+    );
+
+    // a negative nonce from js is rejected rather than panicking
+    assert!(build_sequential_signatures(
+      mnemonic.to_string().into(),
+      1,
+      2,
+      31337,
+      "0x".to_string(),
+      1,
+      "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+      "1000000000".to_string(),
+      "21000".to_string(),
+      2,
+      Some(vec![5, -1]),
+      None,
+      None,
+    )
+    .await
+    .is_err());
+  }
+
+  #[tokio::test]
+  async fn test_build_typed_signatures() {
+    let mnemonic = "test test test test test test test test test test test junk";
+    let storage_key = format!("0x{}", "00".repeat(31) + "01");
+
+    for tx_type in [1u32, 2] {
+      let signed = build_signatures(
+        mnemonic.to_string().into(),
+        1,
+        1,
+        31337,
+        "0x1234".to_string(),
+        3,
+        1,
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+        "1000000000".to_string(),
+        "21000".to_string(),
+        Some(TxOptions {
+          tx_type: Some(tx_type),
+          max_fee_per_gas: Some("3000000000".to_string()),
+          max_priority_fee_per_gas: Some("2000000000".to_string()),
+          access_list: Some(vec![AccessListEntry {
+            address: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+            storage_keys: vec![storage_key.clone()],
+          }]),
+        }),
+        None,
+      )
+      .await
+      .unwrap();
+
+      let raw = hex::decode(&signed[0][2..]).unwrap();
+      assert_eq!(raw[0] as u32, tx_type);
+
+      let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+      assert_eq!(
+        signature.recover(tx.sighash()).unwrap(),
+        get_child_signer(mnemonic, 1, 31337).address()
+      );
+      assert_eq!(tx.nonce(), Some(&3.into()));
+      assert_eq!(tx.chain_id(), Some(31337.into()));
+      assert_eq!(tx.data().unwrap().to_vec(), vec![0x12, 0x34]);
+      assert_eq!(tx.access_list().unwrap().0[0].storage_keys.len(), 1);
+
+      match tx {
+        TypedTransaction::Eip2930(tx) => {
+          assert_eq!(tx.tx.gas_price, Some(U256::from(0x1000000000u64)))
+        }
+        TypedTransaction::Eip1559(tx) => {
+          assert_eq!(tx.max_fee_per_gas, Some(U256::from(0x3000000000u64)));
+          assert_eq!(
+            tx.max_priority_fee_per_gas,
+            Some(U256::from(0x2000000000u64))
+          );
+        }
+        _ => panic!("unexpected tx type"),
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn test_build_native_token_transfer_signatures() {
+    let mnemonic = "test test test test test test test test test test test junk";
+    let build = |start_index: i64, end_index: i64, nonce: Option<i64>| {
+      build_native_token_transfer_signatures(
+        mnemonic.to_string().into(),
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+        31337,
+        start_index,
+        end_index,
+        1,
+        "0x3b9aca00".to_string(),
+        "0x5208".to_string(),
+        None,
+        nonce,
+      )
+    };
+
+    for (start_index, end_index, nonce) in [(0, 0, None), (1, 3, Some(4)), (5, 7, Some(0))] {
+      let senders_and_nonces: Vec<(Address, U256)> = build(start_index, end_index, nonce)
+        .await
+        .unwrap()
+        .iter()
+        .map(|raw| {
+          let raw = hex::decode(&raw[2..]).unwrap();
+          let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+          (
+            signature.recover(tx.sighash()).unwrap(),
+            *tx.nonce().unwrap(),
+          )
+        })
+        .collect();
+
+      let expected: Vec<(Address, U256)> = (start_index..=end_index)
+        .map(|i| {
+          (
+            get_child_signer(mnemonic, i as u32, 31337).address(),
+            nonce.unwrap_or(0).into(),
+          )
+        })
+        .collect();
+      assert_eq!(senders_and_nonces, expected);
+    }
+
+    assert!(build(-1, 2, None).await.is_err());
+    assert!(build(3, 2, None).await.is_err());
+    assert!(build(1, 2, Some(-1)).await.is_err());
+  }
+}
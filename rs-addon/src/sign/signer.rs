@@ -1,69 +1,365 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
 use coins_bip32::ecdsa::SigningKey;
+use coins_bip32::path::DerivationPath;
 use coins_bip32::xkeys::{Parent, XPriv};
 use ethers::prelude::*;
 use ethers::signers::coins_bip39::Mnemonic;
 use ethers::signers::{coins_bip39::English, LocalWallet};
+use ethers::utils::keccak256;
+#[cfg(feature = "napi")]
+use napi::bindgen_prelude::Either;
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::keyring::{keyring, Keyring};
 
 const DERIVE_PATH: &str = "m/44'/60'/0'/0";
 
-pub async fn get_child_signers_with_range(
-  root_menmonic: &str,
-  chain_id: u64,
-  start_index: i64,
-  end_index: i64,
-) -> Vec<LocalWallet> {
-  let mut set = tokio::task::JoinSet::new();
-  let mnemonic = Mnemonic::<English>::new_from_phrase(root_menmonic).unwrap();
-  let root_priv = mnemonic.derive_key(DERIVE_PATH, None).unwrap();
+// replaced by the account index in paths deriving every account on a path of its own, e.g. ledger
+// live's "m/44'/60'/{index}'/0/0"
+pub const INDEX_PLACEHOLDER: &str = "{index}";
 
-  for index in start_index..=end_index {
-    let root_priv = root_priv.clone();
-    set.spawn_blocking(move || {
-      (
-        get_child_signer_with_priv_key(root_priv, index as u32, chain_id),
-        index,
-      )
-    });
+// where the accounts of a mnemonic sit in its hd tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Derivation {
+  // parent path of the accounts, or a path holding INDEX_PLACEHOLDER
+  path: String,
+  // account i is derived at index `index_offset + i`, so generators sharing a mnemonic can each
+  // take their own accounts
+  index_offset: u32,
+}
+
+impl Default for Derivation {
+  fn default() -> Self {
+    Derivation {
+      path: DERIVE_PATH.to_string(),
+      index_offset: 0,
+    }
   }
+}
 
-  let mut results = vec![];
-  while let Some(res) = set.join_next().await {
-    let idx = res.unwrap();
-    results.push(idx);
+impl Derivation {
+  // `account` picks the hardened account level of the default path, `derive_path` replaces the
+  // whole path
+  pub fn new(
+    derive_path: Option<String>,
+    account: Option<u32>,
+    index_offset: Option<u32>,
+  ) -> Result<Self> {
+    let path = match (derive_path, account) {
+      (Some(_), Some(_)) => bail!("account can't be combined with a derive path"),
+      (Some(path), None) => path,
+      (None, account) => format!("m/44'/60'/{}'/0", account.unwrap_or(0)),
+    };
+    path
+      .replace(INDEX_PLACEHOLDER, "0")
+      .parse::<DerivationPath>()
+      .with_context(|| format!("Invalid derive path: {}", path))?;
+
+    Ok(Derivation {
+      path,
+      index_offset: index_offset.unwrap_or(0),
+    })
   }
 
-  results.sort_by(|a, b| a.1.cmp(&b.1));
+  // the key every account is derived from: their common parent, or the master key when each
+  // account has its own path
+  fn root_key(&self, mnemonic: &Mnemonic<English>) -> Result<XPriv> {
+    let master = mnemonic
+      .master_key(None)
+      .with_context(|| "Failed to derive master key".to_string())?;
+    if self.path.contains(INDEX_PLACEHOLDER) {
+      return Ok(master);
+    }
+
+    master
+      .derive_path(self.path.as_str())
+      .with_context(|| format!("Failed to derive {}", self.path))
+  }
 
-  results.clone().into_iter().map(|r| r.0.clone()).collect()
+  fn derive_account(&self, root_key: &XPriv, index: u32) -> Result<XPriv> {
+    if self.path.contains(INDEX_PLACEHOLDER) {
+      let path = self.path.replace(INDEX_PLACEHOLDER, &index.to_string());
+      return Ok(root_key.derive_path(path.as_str())?);
+    }
+    Ok(root_key.derive_child(index)?)
+  }
 }
 
-pub async fn get_child_signers(
+// accounts of the range, only the ones missing from the keyring are derived (and then cached), so
+// ranges used again by funding and signing don't derive their keys twice
+pub async fn get_child_signers_with_range(
   root_menmonic: &str,
-  total_sub_accounts: i64,
+  derivation: &Derivation,
   chain_id: u64,
-) -> Vec<LocalWallet> {
-  get_child_signers_with_range(root_menmonic, chain_id, 0, total_sub_accounts - 1).await
+  range: AccountRange,
+) -> Result<Vec<LocalWallet>> {
+  let range = range.offset(derivation.index_offset)?;
+  let tree = Keyring::tree_id(root_menmonic, &derivation.path);
+  let mut signers: Vec<Option<LocalWallet>> = {
+    let keyring = keyring();
+    range
+      .indexes()
+      .map(|index| keyring.get(tree, index).cloned())
+      .collect()
+  };
+  let missing: Vec<u32> = range
+    .indexes()
+    .zip(&signers)
+    .filter(|(_, signer)| signer.is_none())
+    .map(|(index, _)| index)
+    .collect();
+
+  if !missing.is_empty() {
+    let mut set = tokio::task::JoinSet::new();
+    let mnemonic = Mnemonic::<English>::new_from_phrase(root_menmonic)
+      .with_context(|| "Failed to parse mnemonic".to_string())?;
+    let root_key = derivation.root_key(&mnemonic)?;
+
+    for index in missing {
+      let (root_key, derivation) = (root_key.clone(), derivation.clone());
+      set.spawn_blocking(move || {
+        let signer = derivation
+          .derive_account(&root_key, index)
+          .and_then(|key| to_signer(&key, chain_id));
+        (signer, index)
+      });
+    }
+
+    let mut derived = Vec::with_capacity(set.len());
+    while let Some(res) = set.join_next().await {
+      let (signer, index) = res.with_context(|| "Failed to join signer task".to_string())?;
+      derived.push((
+        signer.with_context(|| format!("Failed to derive account {}", index))?,
+        index,
+      ));
+    }
+
+    let mut keyring = keyring();
+    for (signer, index) in derived {
+      keyring.insert(tree, index, signer.clone());
+      signers[(index - range.indexes().start()) as usize] = Some(signer);
+    }
+  }
+
+  Ok(
+    signers
+      .into_iter()
+      .flatten()
+      .map(|signer| signer.with_chain_id(chain_id))
+      .collect(),
+  )
 }
 
-pub fn get_child_signer_with_priv_key(priv_key: XPriv, index: u32, chain_id: u64) -> LocalWallet {
-  let derived_priv_key = priv_key.derive_child(index).unwrap();
-  let key: &coins_bip32::prelude::SigningKey = derived_priv_key.as_ref();
-  let signer = SigningKey::from_bytes(&key.to_bytes()).unwrap();
+fn to_signer(key: &XPriv, chain_id: u64) -> Result<LocalWallet> {
+  let key: &coins_bip32::prelude::SigningKey = key.as_ref();
+  let signer = SigningKey::from_bytes(&key.to_bytes())?;
 
-  Wallet::<SigningKey>::from(signer).with_chain_id(chain_id)
+  Ok(Wallet::<SigningKey>::from(signer).with_chain_id(chain_id))
 }
 
+// derives a single account without going through the keyring, the reference for tests
+#[cfg(test)]
 pub fn get_child_signer(root_mnemonic: &str, index: u32, chain_id: u64) -> LocalWallet {
   let mnemonic = Mnemonic::<English>::new_from_phrase(root_mnemonic).unwrap();
   let root_priv = mnemonic.derive_key(DERIVE_PATH, None).unwrap();
 
-  get_child_signer_with_priv_key(root_priv, index, chain_id)
+  to_signer(&root_priv.derive_child(index).unwrap(), chain_id).unwrap()
+}
+
+// where the accounts of a signing or funding call come from, exactly one of the sources is set.
+// Account indexes of private keys and keystores are their positions in the list
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct SignerSource {
+  // account i is the i-th child of the hd wallet
+  pub mnemonic: Option<String>,
+  // hex, with or without 0x
+  pub private_keys: Option<Vec<String>>,
+  // v3 json keystore files, or directories of them read in file name order
+  pub keystores: Option<Vec<String>>,
+  pub keystore_password: Option<String>,
+  // mnemonic only, parent path of the accounts (INDEX_PLACEHOLDER marks a per account level),
+  // defaults to "m/44'/60'/{account}'/0"
+  pub derive_path: Option<String>,
+  // mnemonic only, hardened account level of the default path, defaults to 0
+  pub account: Option<u32>,
+  // account i is the source's account `index_offset + i`, defaults to 0
+  pub index_offset: Option<u32>,
+}
+
+impl From<String> for SignerSource {
+  fn from(mnemonic: String) -> Self {
+    SignerSource {
+      mnemonic: Some(mnemonic),
+      ..Default::default()
+    }
+  }
+}
+
+// napi entry points still take a plain mnemonic
+#[cfg(feature = "napi")]
+impl From<Either<String, SignerSource>> for SignerSource {
+  fn from(source: Either<String, SignerSource>) -> Self {
+    match source {
+      Either::A(mnemonic) => mnemonic.into(),
+      Either::B(source) => source,
+    }
+  }
+}
+
+impl SignerSource {
+  pub async fn signers(&self, chain_id: u64, range: AccountRange) -> Result<Vec<LocalWallet>> {
+    if self.mnemonic.is_none() && (self.derive_path.is_some() || self.account.is_some()) {
+      bail!("derive path and account only apply to mnemonics");
+    }
+
+    match (&self.mnemonic, &self.private_keys, &self.keystores) {
+      (Some(mnemonic), None, None) => {
+        let derivation =
+          Derivation::new(self.derive_path.clone(), self.account, self.index_offset)?;
+        get_child_signers_with_range(mnemonic, &derivation, chain_id, range).await
+      }
+      (None, Some(private_keys), None) => {
+        select_range(private_keys, self.offset(range)?, "private keys")?
+          .iter()
+          .map(|key| {
+            key
+              .parse::<LocalWallet>()
+              .map(|signer| signer.with_chain_id(chain_id))
+              .with_context(|| "Failed to parse private key".to_string())
+          })
+          .collect()
+      }
+      (None, None, Some(keystores)) => {
+        let password = self
+          .keystore_password
+          .as_deref()
+          .with_context(|| "keystores require a keystore password".to_string())?;
+        let files = list_keystores(keystores)?;
+        get_keystore_signers(
+          select_range(&files, self.offset(range)?, "keystores")?,
+          password,
+          chain_id,
+        )
+        .await
+      }
+      _ => bail!("signer source should have exactly one of mnemonic, private keys or keystores"),
+    }
+  }
+
+  fn offset(&self, range: AccountRange) -> Result<AccountRange> {
+    range.offset(self.index_offset.unwrap_or(0))
+  }
+
+  pub async fn signer(&self, index: u32, chain_id: u64) -> Result<LocalWallet> {
+    let range = AccountRange::new(index as i64, index as i64)?;
+    Ok(self.signers(chain_id, range).await?.remove(0))
+  }
+}
+
+fn select_range<'a, T>(items: &'a [T], range: AccountRange, name: &str) -> Result<&'a [T]> {
+  let indexes = range.indexes();
+  items
+    .get(*indexes.start() as usize..=*indexes.end() as usize)
+    .with_context(|| {
+      format!(
+        "accounts {} - {} are out of the {} {}",
+        indexes.start(),
+        indexes.end(),
+        items.len(),
+        name
+      )
+    })
+}
+
+fn list_keystores(paths: &[String]) -> Result<Vec<PathBuf>> {
+  let mut files = vec![];
+  for path in paths {
+    let path = PathBuf::from(path);
+    if !path.is_dir() {
+      files.push(path);
+      continue;
+    }
+
+    let mut entries = std::fs::read_dir(&path)
+      .with_context(|| format!("Failed to read keystore directory: {}", path.display()))?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<std::io::Result<Vec<_>>>()?;
+    entries.retain(|entry| entry.is_file());
+    entries.sort();
+    files.extend(entries);
+  }
+  Ok(files)
+}
+
+// keystores are slow to decrypt on purpose, so they are decrypted in parallel and cached in the
+// keyring like derived keys. They are cached by content rather than path, so a keystore replaced
+// in place is decrypted again
+async fn get_keystore_signers(
+  files: &[PathBuf],
+  password: &str,
+  chain_id: u64,
+) -> Result<Vec<LocalWallet>> {
+  let trees = files
+    .iter()
+    .map(|file| {
+      let content = std::fs::read(file)
+        .with_context(|| format!("Failed to read keystore: {}", file.display()))?;
+      let digest = H256::from(keccak256(content));
+      Ok(Keyring::tree_id(
+        password,
+        &format!("keystore:{:?}", digest),
+      ))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  let mut signers: Vec<Option<LocalWallet>> = {
+    let keyring = keyring();
+    trees
+      .iter()
+      .map(|tree| keyring.get(*tree, 0).cloned())
+      .collect()
+  };
+
+  let mut set = tokio::task::JoinSet::new();
+  for (i, file) in files.iter().enumerate() {
+    if signers[i].is_some() {
+      continue;
+    }
+    let (file, password) = (file.clone(), password.to_string());
+    set.spawn_blocking(move || {
+      let signer = LocalWallet::decrypt_keystore(&file, password)
+        .with_context(|| format!("Failed to decrypt keystore: {}", file.display()));
+      (i, signer)
+    });
+  }
+
+  while let Some(res) = set.join_next().await {
+    let (i, signer) = res.with_context(|| "Failed to join keystore task".to_string())?;
+    let signer = signer?;
+    keyring().insert(trees[i], 0, signer.clone());
+    signers[i] = Some(signer);
+  }
+
+  Ok(
+    signers
+      .into_iter()
+      .flatten()
+      .map(|signer| signer.with_chain_id(chain_id))
+      .collect(),
+  )
 }
 
 #[cfg(test)]
 mod tests {
 
-  use crate::sign::signer::get_child_signer;
+  use crate::sign::account_range::AccountRange;
+  use crate::sign::signer::{
+    get_child_signer, get_child_signers_with_range, Derivation, SignerSource,
+  };
+  use ethers::core::rand::thread_rng;
+  use ethers::signers::coins_bip39::English;
   use ethers::{abi::Address, prelude::*};
 
   #[tokio::test]
@@ -87,4 +383,172 @@ mod tests {
         .unwrap()
     )
   }
+
+  #[tokio::test]
+  async fn test_get_child_signers_with_range() {
+    let root_mnemonic = "test test test test test test test test test test test junk";
+
+    // the second range is partly served by the keys cached by the first one
+    for (start_index, end_index, chain_id) in [(3, 5, 1), (4, 6, 31337)] {
+      let range = AccountRange::new(start_index, end_index).unwrap();
+      let signers =
+        get_child_signers_with_range(root_mnemonic, &Derivation::default(), chain_id, range)
+          .await
+          .unwrap();
+
+      let addresses: Vec<Address> = signers.iter().map(|signer| signer.address()).collect();
+      let expected: Vec<Address> = (start_index..=end_index)
+        .map(|i| get_child_signer(root_mnemonic, i as u32, chain_id).address())
+        .collect();
+      assert_eq!(addresses, expected);
+      assert!(signers.iter().all(|signer| signer.chain_id() == chain_id));
+    }
+  }
+
+  #[tokio::test]
+  async fn test_signer_sources() {
+    // hardhat accounts 0 and 1
+    let keys = [
+      "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+      "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    ];
+    let root_mnemonic = "test test test test test test test test test test test junk";
+    let expected: Vec<Address> = (0..2)
+      .map(|i| get_child_signer(root_mnemonic, i, 31337).address())
+      .collect();
+    let range = AccountRange::new(0, 1).unwrap();
+    let addresses = |signers: Vec<LocalWallet>| -> Vec<Address> {
+      signers.iter().map(|signer| signer.address()).collect()
+    };
+
+    let private_keys = SignerSource {
+      private_keys: Some(keys.iter().map(|key| key.to_string()).collect()),
+      ..Default::default()
+    };
+    assert_eq!(
+      addresses(private_keys.signers(31337, range).await.unwrap()),
+      expected
+    );
+    assert_eq!(
+      private_keys.signer(1, 31337).await.unwrap().address(),
+      expected[1]
+    );
+    assert!(private_keys
+      .signers(31337, AccountRange::new(1, 2).unwrap())
+      .await
+      .is_err());
+
+    let dir = std::env::temp_dir().join(format!("rs-addon-keystores-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, key) in ["a", "b"].iter().zip(keys) {
+      let key = hex::decode(key.trim_start_matches("0x")).unwrap();
+      LocalWallet::encrypt_keystore(&dir, &mut thread_rng(), key, "secret", Some(name)).unwrap();
+    }
+    let keystores = |password: &str| SignerSource {
+      keystores: Some(vec![dir.to_string_lossy().to_string()]),
+      keystore_password: Some(password.to_string()),
+      ..Default::default()
+    };
+    assert_eq!(
+      addresses(keystores("secret").signers(31337, range).await.unwrap()),
+      expected
+    );
+    assert!(keystores("wrong").signers(31337, range).await.is_err());
+
+    // a keystore replaced at the same path isn't served from the keyring
+    let key = hex::decode(keys[0]).unwrap();
+    LocalWallet::encrypt_keystore(&dir, &mut thread_rng(), key, "secret", Some("b")).unwrap();
+    assert_eq!(
+      addresses(keystores("secret").signers(31337, range).await.unwrap()),
+      vec![expected[0], expected[0]]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(SignerSource::default().signers(1, range).await.is_err());
+    let both = SignerSource {
+      mnemonic: Some(root_mnemonic.to_string()),
+      ..private_keys
+    };
+    assert!(both.signers(1, range).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_derivation() {
+    let root_mnemonic = "test test test test test test test test test test test junk";
+    let reference = |path: &str| {
+      MnemonicBuilder::<English>::default()
+        .phrase(root_mnemonic)
+        .derivation_path(path)
+        .unwrap()
+        .build()
+        .unwrap()
+        .address()
+    };
+    let source =
+      |derive_path: Option<&str>, account: Option<u32>, index_offset: Option<u32>| SignerSource {
+        derive_path: derive_path.map(str::to_string),
+        account,
+        index_offset,
+        ..SignerSource::from(root_mnemonic.to_string())
+      };
+    let address = |source: SignerSource, index: u32| async move {
+      source.signer(index, 1).await.map(|signer| signer.address())
+    };
+
+    assert_eq!(
+      address(source(None, Some(1), None), 2).await.unwrap(),
+      reference("m/44'/60'/1'/0/2")
+    );
+    assert_eq!(
+      address(source(None, None, Some(5)), 1).await.unwrap(),
+      get_child_signer(root_mnemonic, 6, 1).address()
+    );
+    // ledger live
+    let ledger_live = Some("m/44'/60'/{index}'/0/0");
+    assert_eq!(
+      address(source(ledger_live, None, None), 0).await.unwrap(),
+      get_child_signer(root_mnemonic, 0, 1).address()
+    );
+    assert_eq!(
+      address(source(ledger_live, None, Some(1)), 2)
+        .await
+        .unwrap(),
+      reference("m/44'/60'/3'/0/0")
+    );
+
+    assert!(address(source(Some("m/44'/x"), None, None), 0)
+      .await
+      .is_err());
+    assert!(address(source(Some("m/44'/60'/0'/0"), Some(1), None), 0)
+      .await
+      .is_err());
+    let private_keys = SignerSource {
+      private_keys: Some(vec![
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string(),
+        "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d".to_string(),
+      ]),
+      ..Default::default()
+    };
+    assert_eq!(
+      address(
+        SignerSource {
+          index_offset: Some(1),
+          ..private_keys.clone()
+        },
+        0
+      )
+      .await
+      .unwrap(),
+      get_child_signer(root_mnemonic, 1, 1).address()
+    );
+    assert!(address(
+      SignerSource {
+        account: Some(1),
+        ..private_keys
+      },
+      0
+    )
+    .await
+    .is_err());
+  }
 }
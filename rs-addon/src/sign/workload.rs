@@ -0,0 +1,395 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use ethers::abi::Abi;
+use ethers::core::rand::rngs::StdRng;
+use ethers::core::rand::SeedableRng;
+use ethers::prelude::*;
+use serde::Deserialize;
+
+use crate::sign::account_range::AccountRange;
+use crate::sign::calldata::{Calldata, CalldataArg};
+use crate::sign::signature::{non_negative, sign_typed_transaction, TxEnvelope, TxOptions};
+use crate::sign::signer::SignerSource;
+
+// a benchmark scenario, e.g.
+// {
+//   "transactions": [{
+//     "name": "erc20 transfer",
+//     "abi": "./GLDToken.json",
+//     "to": "0x...",
+//     "function": "transfer",
+//     "args": [{ "kind": "random_address" }, { "kind": "random_amount", "max": "1000" }],
+//     "weight": 3
+//   }]
+// }
+#[derive(Deserialize)]
+pub struct WorkloadSpec {
+  pub transactions: Vec<TransactionSpec>,
+}
+
+#[derive(Deserialize)]
+pub struct TransactionSpec {
+  pub name: Option<String>,
+  // a path to an abi json (relative to the spec file), or the abi inline; both may be a plain abi
+  // array or a compiler artifact holding it under `abi`
+  pub abi: Option<serde_json::Value>,
+  pub to: String,
+  // function name in the abi, overloads are told apart by their number of args. Plain transfers
+  // leave it out
+  pub function: Option<String>,
+  #[serde(default)]
+  pub args: Vec<CalldataArg>,
+  // wei, decimal
+  pub value: Option<String>,
+  // share of the accounts sending this transaction, defaults to 1
+  pub weight: Option<u32>,
+  // overrides the round gas limit, decimal
+  pub gas_limit: Option<String>,
+}
+
+impl WorkloadSpec {
+  pub fn load(path: &str) -> Result<Self> {
+    let content = std::fs::read_to_string(path)
+      .with_context(|| format!("Failed to read workload: {}", path))?;
+    let mut spec: WorkloadSpec = serde_json::from_str(&content)
+      .with_context(|| format!("Failed to parse workload: {}", path))?;
+
+    // resolve abi paths now, so the spec no longer depends on where it was loaded from
+    let base = Path::new(path).parent().unwrap_or(Path::new("."));
+    for tx in spec.transactions.iter_mut() {
+      if let Some(serde_json::Value::String(abi_path)) = &tx.abi {
+        let abi_path: PathBuf = base.join(abi_path);
+        let content = std::fs::read_to_string(&abi_path)
+          .with_context(|| format!("Failed to read abi: {}", abi_path.display()))?;
+        tx.abi = Some(
+          serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse abi: {}", abi_path.display()))?,
+        );
+      }
+    }
+
+    Ok(spec)
+  }
+}
+
+// a transaction spec resolved against its abi, ready to produce one transaction per signer
+pub struct WorkloadTransaction {
+  pub name: String,
+  pub to: Address,
+  pub value: U256,
+  pub gas_limit: Option<U256>,
+  pub weight: u32,
+  calldata: Option<Calldata>,
+}
+
+impl WorkloadTransaction {
+  pub fn from_spec(spec: &TransactionSpec) -> Result<Self> {
+    let name = spec
+      .name
+      .clone()
+      .or_else(|| spec.function.clone())
+      .unwrap_or_else(|| "transfer".to_string());
+
+    let calldata = match &spec.function {
+      Some(function) => {
+        let abi = spec
+          .abi
+          .as_ref()
+          .with_context(|| format!("{} calls {} but has no abi", name, function))?;
+        let abi = parse_abi(abi).with_context(|| format!("Invalid abi of {}", name))?;
+        let function = abi
+          .functions_by_name(function)
+          .with_context(|| format!("{} not found in the abi of {}", function, name))?
+          .iter()
+          .find(|f| f.inputs.len() == spec.args.len())
+          .with_context(|| {
+            format!(
+              "no {} taking {} args in the abi of {}",
+              function,
+              spec.args.len(),
+              name
+            )
+          })?
+          .clone();
+        Some(Calldata::new(function, &spec.args).with_context(|| format!("Invalid {}", name))?)
+      }
+      None => None,
+    };
+
+    let parse_dec = |field: &str, value: &Option<String>| -> Result<Option<U256>> {
+      value
+        .as_deref()
+        .map(U256::from_dec_str)
+        .transpose()
+        .with_context(|| format!("Failed to parse {} of {}", field, name))
+    };
+
+    Ok(WorkloadTransaction {
+      to: spec
+        .to
+        .parse::<Address>()
+        .with_context(|| format!("Failed to parse to address of {}", name))?,
+      value: parse_dec("value", &spec.value)?.unwrap_or_default(),
+      gas_limit: parse_dec("gas limit", &spec.gas_limit)?,
+      weight: spec.weight.unwrap_or(1),
+      calldata,
+      name,
+    })
+  }
+
+  pub fn request(
+    &mut self,
+    account_index: i64,
+    account: Address,
+    rng: &mut StdRng,
+  ) -> Result<TransactionRequest> {
+    let mut tx = TransactionRequest::new().to(self.to).value(self.value);
+    if let Some(calldata) = self.calldata.as_mut() {
+      tx = tx.data(calldata.encode(account_index, account, rng)?);
+    }
+    if let Some(gas_limit) = self.gas_limit {
+      tx = tx.gas(gas_limit);
+    }
+
+    Ok(tx)
+  }
+}
+
+fn parse_abi(value: &serde_json::Value) -> Result<Abi> {
+  let value = match value.get("abi") {
+    Some(abi) => abi,
+    None => value,
+  };
+
+  Ok(serde_json::from_value(value.clone())?)
+}
+
+// splits `total` into shares proportional to `weights` (largest remainder), so the counts always
+// add up to `total`
+pub fn apportion(weights: &[u32], total: usize) -> Result<Vec<usize>> {
+  let weight_sum: u64 = weights.iter().map(|&w| w as u64).sum();
+  if weight_sum == 0 {
+    bail!("at least one transaction should have a positive weight");
+  }
+
+  let mut counts: Vec<usize> = weights
+    .iter()
+    .map(|&w| (w as u64 * total as u64 / weight_sum) as usize)
+    .collect();
+
+  let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+  by_remainder.sort_by_key(|&i| std::cmp::Reverse(weights[i] as u64 * total as u64 % weight_sum));
+  let assigned: usize = counts.iter().sum();
+  for &i in by_remainder.iter().take(total - assigned) {
+    counts[i] += 1;
+  }
+
+  Ok(counts)
+}
+
+// signs one transaction per account in the range, handing consecutive accounts to the
+// transactions of the workload in proportion to their weights. Random arguments are drawn from
+// `seed` when set, so the same seed always gives the same transactions
+pub async fn build_workload_signatures(
+  accounts: SignerSource,
+  start_index: i64,
+  end_index: i64,
+  chain_id: i64,
+  nonce: i64,
+  gas_price: String,
+  gas_limit: String,
+  workload_path: String,
+  tx_options: Option<TxOptions>,
+  seed: Option<i64>,
+) -> Result<Vec<String>> {
+  let spec = WorkloadSpec::load(&workload_path)?;
+  let mut transactions = spec
+    .transactions
+    .iter()
+    .map(WorkloadTransaction::from_spec)
+    .collect::<Result<Vec<_>>>()?;
+
+  let nonce = non_negative("nonce", nonce)?;
+  let envelope = TxEnvelope::from_options(tx_options)?;
+  let gas_price = gas_price
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas price".to_string())?;
+  let gas_limit = gas_limit
+    .parse::<U256>()
+    .with_context(|| "Failed to parse gas limit".to_string())?;
+
+  let range = AccountRange::new(start_index, end_index)?;
+  let signers = accounts.signers(chain_id as u64, range).await?;
+  let weights: Vec<u32> = transactions.iter().map(|tx| tx.weight).collect();
+  let counts = apportion(&weights, signers.len())?;
+
+  let mut rng = match seed {
+    Some(seed) => StdRng::seed_from_u64(seed as u64),
+    None => StdRng::from_entropy(),
+  };
+  let mut signed_tx = Vec::with_capacity(signers.len());
+  let mut signers = signers.iter().zip(start_index..);
+  for (transaction, count) in transactions.iter_mut().zip(counts) {
+    log::info!("[workload] {}: {} txs", transaction.name, count);
+    if let Some(calldata) = &transaction.calldata {
+      calldata
+        .check_count(count)
+        .with_context(|| format!("Invalid {}", transaction.name))?;
+    }
+
+    for (signer, index) in signers.by_ref().take(count) {
+      let mut tx = transaction
+        .request(index, signer.address(), &mut rng)?
+        .nonce(nonce)
+        .gas_price(gas_price);
+      if tx.gas.is_none() {
+        tx = tx.gas(gas_limit);
+      }
+
+      signed_tx.push(sign_typed_transaction(signer, envelope.wrap(tx)).await?);
+    }
+  }
+
+  anyhow::Ok(signed_tx)
+}
+
+#[cfg(test)]
+mod tests {
+  use ethers::abi::Token;
+  use ethers::prelude::*;
+  use ethers::types::transaction::eip2718::TypedTransaction;
+  use ethers::utils::rlp::Rlp;
+
+  use crate::sign::workload::{apportion, build_workload_signatures};
+
+  #[test]
+  fn test_apportion() {
+    assert_eq!(apportion(&[1], 5).unwrap(), vec![5]);
+    assert_eq!(apportion(&[6, 3, 1], 10).unwrap(), vec![6, 3, 1]);
+    assert_eq!(apportion(&[1, 1, 1], 10).unwrap(), vec![4, 3, 3]);
+    assert_eq!(apportion(&[0, 2], 3).unwrap(), vec![0, 3]);
+    assert!(apportion(&[0], 3).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_build_workload_signatures() {
+    let dir = std::env::temp_dir().join(format!("rs-addon-workload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+      dir.join("token.json"),
+      r#"{"contractName":"Token","abi":[{"type":"function","name":"transfer","stateMutability":"nonpayable","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"name":"","type":"bool"}]}]}"#,
+    )
+    .unwrap();
+    std::fs::write(
+      dir.join("workload.json"),
+      r#"{
+        "transactions": [
+          {
+            "name": "erc20 transfer",
+            "abi": "token.json",
+            "to": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            "function": "transfer",
+            "args": [{ "kind": "account_address" }, { "kind": "counter", "start": "7" }],
+            "weight": 2
+          },
+          {
+            "to": "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+            "value": "1000",
+            "gas_limit": "21000"
+          }
+        ]
+      }"#,
+    )
+    .unwrap();
+
+    let build = |nonce: i64| {
+      build_workload_signatures(
+        "test test test test test test test test test test test junk"
+          .to_string()
+          .into(),
+        1,
+        3,
+        31337,
+        nonce,
+        "0x3b9aca00".to_string(),
+        "0x186a0".to_string(),
+        dir.join("workload.json").to_string_lossy().to_string(),
+        None,
+        None,
+      )
+    };
+    let signed = build(0).await.unwrap();
+    assert!(build(-1).await.is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let txs: Vec<(TypedTransaction, Address)> = signed
+      .iter()
+      .map(|raw| {
+        let raw = hex::decode(&raw[2..]).unwrap();
+        let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        let from = signature.recover(tx.sighash()).unwrap();
+        (tx, from)
+      })
+      .collect();
+    assert_eq!(txs.len(), 3);
+
+    let function = ethers::abi::AbiParser::default()
+      .parse_function("transfer(address,uint256)")
+      .unwrap();
+    for (i, (tx, from)) in txs[..2].iter().enumerate() {
+      let input = function.decode_input(&tx.data().unwrap()[4..]).unwrap();
+      assert_eq!(
+        input,
+        vec![Token::Address(*from), Token::Uint((7 + i).into())]
+      );
+      assert_eq!(tx.gas(), Some(&U256::from(100000)));
+    }
+
+    let (transfer, _) = &txs[2];
+    assert_eq!(transfer.value(), Some(&U256::from(1000)));
+    assert_eq!(transfer.gas(), Some(&U256::from(21000)));
+    assert!(transfer.data().is_none_or(|data| data.is_empty()));
+  }
+
+  #[tokio::test]
+  async fn test_workload_seed() {
+    let dir = std::env::temp_dir().join(format!("rs-addon-workload-seed-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("workload.json");
+    std::fs::write(
+      &path,
+      r#"{
+        "transactions": [{
+          "abi": [{"type":"function","name":"transfer","stateMutability":"nonpayable","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[]}],
+          "to": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+          "function": "transfer",
+          "args": [{ "kind": "random_address" }, { "kind": "random_amount" }]
+        }]
+      }"#,
+    )
+    .unwrap();
+
+    let build = |seed: Option<i64>| {
+      build_workload_signatures(
+        "test test test test test test test test test test test junk"
+          .to_string()
+          .into(),
+        1,
+        2,
+        31337,
+        0,
+        "0x3b9aca00".to_string(),
+        "0x186a0".to_string(),
+        path.to_string_lossy().to_string(),
+        None,
+        seed,
+      )
+    };
+    let seeded = build(Some(7)).await.unwrap();
+    assert_eq!(build(Some(7)).await.unwrap(), seeded);
+    assert_ne!(build(Some(8)).await.unwrap(), seeded);
+    assert_ne!(build(None).await.unwrap(), seeded);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
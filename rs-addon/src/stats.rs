@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use hdrhistogram::Histogram;
+use serde::Serialize;
+
+use crate::transaction::{Receipt, SendError};
+
+const DEFAULT_BUCKET_MS: u32 = 1000;
+// latencies are recorded in microseconds with 3 significant figures, up to an hour
+const HISTOGRAM_SIGFIG: u8 = 3;
+const HISTOGRAM_MAX_MICROS: u64 = 3_600_000_000;
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Serialize)]
+pub struct LatencyStats {
+  // all in milliseconds
+  pub min: f64,
+  pub mean: f64,
+  pub p50: f64,
+  pub p90: f64,
+  pub p99: f64,
+  pub p999: f64,
+  pub max: f64,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Serialize)]
+pub struct BlockStats {
+  pub block_number: i64,
+  pub block_timestamp: i64,
+  pub tx_count: u32,
+  pub gas_used: f64,
+  // none when the block time can't be told apart, e.g. several blocks in the same second
+  pub tps: Option<f64>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Serialize)]
+pub struct TimeBucket {
+  // unix timestamp in milliseconds
+  pub start: f64,
+  pub sent: u32,
+  pub confirmed: u32,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Serialize)]
+pub struct FailureCount {
+  pub category: String,
+  pub count: u32,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Serialize)]
+pub struct RoundStats {
+  pub total: u32,
+  pub success: u32,
+  pub reverted: u32,
+  pub failed: u32,
+  pub success_ratio: f64,
+  // confirmed transactions per second, from the first send to the last confirmation
+  pub tps: f64,
+  pub send_latency: LatencyStats,
+  pub response_latency: LatencyStats,
+  pub blocks: Vec<BlockStats>,
+  pub buckets: Vec<TimeBucket>,
+  pub failures: Vec<FailureCount>,
+}
+
+#[derive(Default)]
+struct BlockAccumulator {
+  timestamp: i64,
+  tx_count: u32,
+  gas_used: f64,
+}
+
+fn new_histogram() -> Histogram<u64> {
+  Histogram::new_with_bounds(1, HISTOGRAM_MAX_MICROS, HISTOGRAM_SIGFIG)
+    .expect("valid histogram bounds")
+}
+
+fn latency_stats(histogram: &Histogram<u64>) -> LatencyStats {
+  let millis = |micros: u64| micros as f64 / 1000.0;
+
+  if histogram.is_empty() {
+    return LatencyStats {
+      min: 0.0,
+      mean: 0.0,
+      p50: 0.0,
+      p90: 0.0,
+      p99: 0.0,
+      p999: 0.0,
+      max: 0.0,
+    };
+  }
+
+  LatencyStats {
+    min: millis(histogram.min()),
+    mean: histogram.mean() / 1000.0,
+    p50: millis(histogram.value_at_quantile(0.5)),
+    p90: millis(histogram.value_at_quantile(0.9)),
+    p99: millis(histogram.value_at_quantile(0.99)),
+    p999: millis(histogram.value_at_quantile(0.999)),
+    max: millis(histogram.max()),
+  }
+}
+
+// per-block tps uses the average block time since the previous block holding round txs
+fn block_stats(blocks: BTreeMap<i64, BlockAccumulator>) -> Vec<BlockStats> {
+  let mut previous: Option<(i64, i64)> = None;
+
+  blocks
+    .into_iter()
+    .map(|(block_number, block)| {
+      let tps = previous.and_then(|(previous_number, previous_timestamp)| {
+        let block_time =
+          (block.timestamp - previous_timestamp) as f64 / (block_number - previous_number) as f64;
+        (block_time > 0.0).then(|| block.tx_count as f64 / block_time)
+      });
+      previous = Some((block_number, block.timestamp));
+
+      BlockStats {
+        block_number,
+        block_timestamp: block.timestamp,
+        tx_count: block.tx_count,
+        gas_used: block.gas_used,
+        tps,
+      }
+    })
+    .collect()
+}
+
+pub fn aggregate(results: &[Result<Receipt, SendError>], bucket_ms: Option<u32>) -> RoundStats {
+  let bucket_ms = bucket_ms.unwrap_or(DEFAULT_BUCKET_MS).max(1) as f64;
+
+  let mut send_latency = new_histogram();
+  let mut response_latency = new_histogram();
+  let mut blocks: BTreeMap<i64, BlockAccumulator> = BTreeMap::new();
+  let mut failures: BTreeMap<&'static str, u32> = BTreeMap::new();
+  let mut events: Vec<(f64, bool)> = vec![];
+  let (mut success, mut reverted) = (0u32, 0u32);
+
+  for result in results {
+    let receipt = match result {
+      Ok(receipt) => receipt,
+      Err(e) => {
+        *failures.entry(e.category.as_str()).or_default() += 1;
+        continue;
+      }
+    };
+
+    if receipt.success {
+      success += 1;
+    } else {
+      reverted += 1;
+    }
+
+    send_latency.saturating_record((receipt.send_time_cost * 1000.0) as u64);
+    response_latency.saturating_record((receipt.response_time_cost * 1000.0) as u64);
+
+    let block = blocks
+      .entry(receipt.block_number.parse().unwrap_or_default())
+      .or_default();
+    block.timestamp = receipt.block_timestamp.parse().unwrap_or_default();
+    block.tx_count += 1;
+    block.gas_used += receipt.gas_used.parse::<f64>().unwrap_or_default();
+
+    events.push((receipt.start_time, false));
+    events.push((receipt.send_time + receipt.response_time_cost, true));
+  }
+
+  let total = results.len() as u32;
+  let failed = total - success - reverted;
+
+  let first_send = events
+    .iter()
+    .filter(|(_, confirmed)| !confirmed)
+    .map(|(time, _)| *time)
+    .fold(f64::INFINITY, f64::min);
+  let last_confirmation = events
+    .iter()
+    .filter(|(_, confirmed)| *confirmed)
+    .map(|(time, _)| *time)
+    .fold(f64::NEG_INFINITY, f64::max);
+  let elapsed_secs = (last_confirmation - first_send) / 1000.0;
+  let tps = if elapsed_secs > 0.0 {
+    success as f64 / elapsed_secs
+  } else {
+    0.0
+  };
+
+  let mut buckets: Vec<TimeBucket> = vec![];
+  if !events.is_empty() {
+    let count = ((last_confirmation - first_send) / bucket_ms).floor() as usize + 1;
+    buckets = (0..count)
+      .map(|i| TimeBucket {
+        start: first_send + i as f64 * bucket_ms,
+        sent: 0,
+        confirmed: 0,
+      })
+      .collect();
+    for (time, confirmed) in events {
+      let index = (((time - first_send) / bucket_ms).floor() as usize).min(count - 1);
+      if confirmed {
+        buckets[index].confirmed += 1;
+      } else {
+        buckets[index].sent += 1;
+      }
+    }
+  }
+
+  RoundStats {
+    total,
+    success,
+    reverted,
+    failed,
+    success_ratio: if total == 0 {
+      0.0
+    } else {
+      success as f64 / total as f64
+    },
+    tps,
+    send_latency: latency_stats(&send_latency),
+    response_latency: latency_stats(&response_latency),
+    blocks: block_stats(blocks),
+    buckets,
+    failures: failures
+      .into_iter()
+      .map(|(category, count)| FailureCount {
+        category: category.to_string(),
+        count,
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::stats::aggregate;
+  use crate::transaction::{Receipt, SendError, SendErrorCategory};
+
+  fn receipt(block_number: i64, block_timestamp: i64, start_time: f64, latency: f64) -> Receipt {
+    Receipt {
+      hash: "0x".to_string(),
+      block_number: block_number.to_string(),
+      block_timestamp: block_timestamp.to_string(),
+      transaction_index: 0,
+      success: true,
+      from: "0x".to_string(),
+      nonce: 0,
+      tx_type: 0,
+      gas_used: "21000".to_string(),
+      cumulative_gas_used: "21000".to_string(),
+      effective_gas_price: "1".to_string(),
+      start_time,
+      send_time: start_time + 10.0,
+      send_time_cost: 10.0,
+      response_time_cost: latency,
+    }
+  }
+
+  #[test]
+  fn test_aggregate() {
+    let results = vec![
+      Ok(receipt(10, 100, 0.0, 990.0)),
+      Ok(receipt(10, 100, 500.0, 490.0)),
+      Ok(receipt(12, 104, 1000.0, 1990.0)),
+      Err(SendError::new(
+        SendErrorCategory::NonceTooLow,
+        3,
+        "nonce too low",
+      )),
+    ];
+
+    let stats = aggregate(&results, Some(1000));
+
+    assert_eq!(stats.total, 4);
+    assert_eq!(stats.success, 3);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.success_ratio, 0.75);
+    // 3 confirmations between 0ms and 3000ms
+    assert_eq!(stats.tps, 1.0);
+
+    // histogram values are exact to 3 significant figures
+    let assert_close = |actual: f64, expected: f64| {
+      assert!((actual - expected).abs() <= expected * 1e-3, "{}", actual)
+    };
+    assert_close(stats.send_latency.p50, 10.0);
+    assert_close(stats.response_latency.min, 490.0);
+    assert_close(stats.response_latency.max, 1990.0);
+
+    assert_eq!(stats.blocks.len(), 2);
+    assert_eq!(stats.blocks[0].tx_count, 2);
+    assert_eq!(stats.blocks[0].tps, None);
+    // 1 tx over 2 blocks of 2 seconds each
+    assert_eq!(stats.blocks[1].tps, Some(0.5));
+
+    let sent: Vec<u32> = stats.buckets.iter().map(|b| b.sent).collect();
+    let confirmed: Vec<u32> = stats.buckets.iter().map(|b| b.confirmed).collect();
+    assert_eq!(sent, vec![2, 1, 0, 0]);
+    assert_eq!(confirmed, vec![0, 2, 0, 1]);
+
+    assert_eq!(stats.failures.len(), 1);
+    assert_eq!(stats.failures[0].category, "nonce_too_low");
+  }
+}
@@ -1,104 +1,527 @@
-use anyhow::{Context, Result};
-use ethers::providers::{Http, Middleware, Provider};
+use anyhow::{bail, Context, Result};
+use ethers::providers::{Middleware, Provider, ProviderError, RpcError};
+use ethers::types::{H256, U256};
+use ethers::utils::keccak256;
+use ethers::utils::rlp::Rlp;
 use hex::ToHex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
 use tokio::task;
+use tokio::time::Instant;
 
-#[napi(object)]
+use crate::provider::{connect_provider, ConnectionOptions, Transport};
+use crate::receipt_tracker::{ReceiptTracker, DEFAULT_BLOCK_POLL_INTERVAL};
+
+const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Receipt {
   pub hash: String,
   pub block_number: String,
+  pub block_timestamp: String,
+  pub transaction_index: u32,
   pub success: bool,
 
-  pub start_time: String,
-  pub send_time: String,
-  pub send_time_cost: String,
-  pub response_time_cost: String,
+  pub from: String,
+  pub nonce: i64,
+  pub tx_type: u32,
+  pub gas_used: String,
+  pub cumulative_gas_used: String,
+  pub effective_gas_price: String,
+
+  // unix timestamps and durations in milliseconds, with microsecond precision
+  pub start_time: f64,
+  pub send_time: f64,
+  pub send_time_cost: f64,
+  pub response_time_cost: f64,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct RateControlOptions {
+  // `none` | `fixed-load` | `maximum-rate`, the `rate_control.type` of the config
+  pub mode: Option<String>,
+  // target tps of `fixed-load`, finishing tps of `maximum-rate`
+  pub tps: Option<f64>,
+  // starting tps of `maximum-rate`, which ramps linearly up to `tps`
+  pub start_tps: Option<f64>,
+  // max transactions waiting for send or receipt at the same time
+  pub max_in_flight: Option<u32>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Default)]
+pub struct SendOptions {
+  pub connection: Option<ConnectionOptions>,
+  pub rate_control: Option<RateControlOptions>,
+  // give up waiting for a receipt after this long, counted from the send
+  pub receipt_timeout_ms: Option<u32>,
+  // how often new blocks are polled for on http providers
+  pub poll_interval_ms: Option<u32>,
+  // blocks including the mining one before a tx counts as confirmed, 1 by default
+  pub confirmations: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateControl {
+  Unlimited,
+  FixedLoad { tps: f64 },
+  MaximumRate { start_tps: f64, end_tps: f64 },
+}
+
+impl RateControl {
+  pub fn from_options(options: &RateControlOptions) -> Result<Self> {
+    let positive = |name: &str, value: Option<f64>| match value {
+      Some(v) if v > 0.0 && v.is_finite() => Ok(v),
+      Some(v) => bail!("rate control `{}` should be positive, got {}", name, v),
+      None => bail!("rate control `{}` is required", name),
+    };
+
+    match options.mode.as_deref().unwrap_or("none") {
+      "none" => Ok(RateControl::Unlimited),
+      "fixed-load" => Ok(RateControl::FixedLoad {
+        tps: positive("tps", options.tps)?,
+      }),
+      "maximum-rate" => Ok(RateControl::MaximumRate {
+        start_tps: positive("startTps", options.start_tps)?,
+        end_tps: positive("tps", options.tps)?,
+      }),
+      mode => bail!("unknown rate control mode: {}", mode),
+    }
+  }
+
+  // offset of every send relative to the start of the round
+  pub fn schedule(&self, total: usize) -> Vec<Duration> {
+    let mut offsets = Vec::with_capacity(total);
+    let mut elapsed = 0.0;
+
+    for i in 0..total {
+      offsets.push(Duration::from_secs_f64(elapsed));
+
+      elapsed += match self {
+        RateControl::Unlimited => 0.0,
+        RateControl::FixedLoad { tps } => 1.0 / tps,
+        RateControl::MaximumRate { start_tps, end_tps } => {
+          let progress = if total > 1 {
+            i as f64 / (total - 1) as f64
+          } else {
+            0.0
+          };
+          1.0 / (start_tps + (end_tps - start_tps) * progress)
+        }
+      };
+    }
+
+    offsets
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SendFailure {
+  pub category: String,
+  pub rpc_code: Option<i64>,
+  pub message: String,
+  pub index: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SendErrorCategory {
+  NonceTooLow,
+  ReplacementUnderpriced,
+  InsufficientFunds,
+  TxpoolFull,
+  AlreadyKnown,
+  HttpTimeout,
+  ReceiptTimeout,
+  Rpc,
+  Transport,
+  InvalidTransaction,
+  Other,
+}
+
+impl SendErrorCategory {
+  const ALL: [SendErrorCategory; 11] = [
+    SendErrorCategory::NonceTooLow,
+    SendErrorCategory::ReplacementUnderpriced,
+    SendErrorCategory::InsufficientFunds,
+    SendErrorCategory::TxpoolFull,
+    SendErrorCategory::AlreadyKnown,
+    SendErrorCategory::HttpTimeout,
+    SendErrorCategory::ReceiptTimeout,
+    SendErrorCategory::Rpc,
+    SendErrorCategory::Transport,
+    SendErrorCategory::InvalidTransaction,
+    SendErrorCategory::Other,
+  ];
+
+  // the category named `name` by `as_str`, unknown names are `Other`
+  pub fn from_name(name: &str) -> Self {
+    Self::ALL
+      .into_iter()
+      .find(|category| category.as_str() == name)
+      .unwrap_or(SendErrorCategory::Other)
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SendErrorCategory::NonceTooLow => "nonce_too_low",
+      SendErrorCategory::ReplacementUnderpriced => "replacement_underpriced",
+      SendErrorCategory::InsufficientFunds => "insufficient_funds",
+      SendErrorCategory::TxpoolFull => "txpool_full",
+      SendErrorCategory::AlreadyKnown => "already_known",
+      SendErrorCategory::HttpTimeout => "http_timeout",
+      SendErrorCategory::ReceiptTimeout => "receipt_timeout",
+      SendErrorCategory::Rpc => "rpc_error",
+      SendErrorCategory::Transport => "transport_error",
+      SendErrorCategory::InvalidTransaction => "invalid_transaction",
+      SendErrorCategory::Other => "other",
+    }
+  }
+
+  // node clients word the same txpool rejection differently, match the common phrasings
+  fn from_message(message: &str) -> Option<Self> {
+    let message = message.to_lowercase();
+    let category = if message.contains("nonce too low") || message.contains("nonce is too low") {
+      SendErrorCategory::NonceTooLow
+    } else if message.contains("underpriced") {
+      SendErrorCategory::ReplacementUnderpriced
+    } else if message.contains("insufficient funds") || message.contains("insufficient balance") {
+      SendErrorCategory::InsufficientFunds
+    } else if message.contains("txpool is full")
+      || message.contains("transaction pool is full")
+      || message.contains("too many transactions")
+    {
+      SendErrorCategory::TxpoolFull
+    } else if message.contains("already known") || message.contains("known transaction") {
+      SendErrorCategory::AlreadyKnown
+    } else {
+      return None;
+    };
+
+    Some(category)
+  }
+}
+
+#[derive(Debug)]
+pub struct SendError {
+  pub category: SendErrorCategory,
+  pub rpc_code: Option<i64>,
+  pub message: String,
+  pub index: usize,
+}
+
+impl SendError {
+  pub fn new(category: SendErrorCategory, index: usize, message: impl ToString) -> Self {
+    SendError {
+      category,
+      rpc_code: None,
+      message: message.to_string(),
+      index,
+    }
+  }
+
+  pub fn from_provider_error(index: usize, error: &ProviderError) -> Self {
+    if let Some(response) = error.as_error_response() {
+      return SendError {
+        category: SendErrorCategory::from_message(&response.message)
+          .unwrap_or(SendErrorCategory::Rpc),
+        rpc_code: Some(response.code),
+        message: response.message.clone(),
+        index,
+      };
+    }
+
+    let category = match error {
+      ProviderError::HTTPError(e) if e.is_timeout() => SendErrorCategory::HttpTimeout,
+      ProviderError::HTTPError(_) | ProviderError::JsonRpcClientError(_) => {
+        SendErrorCategory::Transport
+      }
+      _ => SendErrorCategory::Other,
+    };
+    SendError::new(category, index, error)
+  }
+}
+
+impl std::fmt::Display for SendError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "tx #{} failed ({}): {}",
+      self.index,
+      self.category.as_str(),
+      self.message
+    )
+  }
+}
+
+impl std::error::Error for SendError {}
+
+impl From<SendError> for SendFailure {
+  fn from(error: SendError) -> Self {
+    SendFailure {
+      category: error.category.as_str().to_string(),
+      rpc_code: error.rpc_code,
+      message: error.message,
+      index: error.index as u32,
+    }
+  }
+}
+
+impl From<SendFailure> for SendError {
+  fn from(failure: SendFailure) -> Self {
+    SendError {
+      category: SendErrorCategory::from_name(&failure.category),
+      rpc_code: failure.rpc_code,
+      message: failure.message,
+      index: failure.index as usize,
+    }
+  }
+}
+
+// Monotonic clock anchored to the wall clock once per round: durations can't go backwards on
+// clock adjustments, and instants still map to unix timestamps.
+#[derive(Clone, Copy)]
+pub struct Clock {
+  anchor: std::time::Instant,
+  anchor_unix_micros: u128,
+}
+
+impl Clock {
+  pub fn new() -> Self {
+    Clock {
+      anchor: std::time::Instant::now(),
+      anchor_unix_micros: SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros(),
+    }
+  }
+
+  pub fn unix_millis(&self, instant: std::time::Instant) -> f64 {
+    let micros =
+      self.anchor_unix_micros + instant.saturating_duration_since(self.anchor).as_micros();
+    micros as f64 / 1000.0
+  }
+}
+
+impl Default for Clock {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+fn millis_between(from: std::time::Instant, to: std::time::Instant) -> f64 {
+  to.saturating_duration_since(from).as_micros() as f64 / 1000.0
 }
 
 pub async fn send_raw_transaction_inner(
-  http_provider: String,
+  provider: Provider<Transport>,
+  tracker: Arc<ReceiptTracker>,
+  clock: Clock,
+  receipt_timeout: Duration,
   tx: String,
-  _index: usize,
-) -> Result<Option<Receipt>> {
+  index: usize,
+) -> std::result::Result<Receipt, SendError> {
   // send raw trnasaction
-  let provider = Provider::<Http>::try_from(http_provider)
-    .with_context(|| "Failed to create provider".to_string())?;
+  let start_time = std::time::Instant::now();
 
-  let mut tx_without_prefix = tx.clone();
-  tx_without_prefix.drain(0..2);
+  let tx = hex::decode(tx.trim_start_matches("0x")).map_err(|e| {
+    SendError::new(
+      SendErrorCategory::InvalidTransaction,
+      index,
+      format!("Failed to decode tx: {}", e),
+    )
+  })?;
 
-  let start_time = SystemTime::now()
-    .duration_since(UNIX_EPOCH)
-    .with_context(|| "Failed to get duration since epoch".to_string())?
-    .as_millis();
+  let nonce = raw_transaction_nonce(&tx).map_err(|e| {
+    SendError::new(
+      SendErrorCategory::InvalidTransaction,
+      index,
+      format!("{:#}", e),
+    )
+  })?;
+  let tx_type = raw_transaction_type(&tx);
 
-  let tx =
-    hex::decode(tx_without_prefix.clone()).with_context(|| "Failed to decode tx".to_string())?;
+  let transaction_hash = raw_transaction_hash(&tx);
+  let tracked_receipt = tracker.track(transaction_hash);
 
-  let pending_tx = provider
-    .send_raw_transaction(tx.into())
-    .await
-    .with_context(|| "Failed to send raw transaction".to_string())?;
+  if let Err(e) = provider.send_raw_transaction(tx.into()).await {
+    tracker.untrack(&transaction_hash);
+    return Err(SendError::from_provider_error(index, &e));
+  }
 
-  let transaction_hash = pending_tx.tx_hash();
+  let send_time = std::time::Instant::now();
 
-  let send_time = SystemTime::now()
-    .duration_since(UNIX_EPOCH)
-    .with_context(|| "Failed to get duration since epoch".to_string())?
-    .as_millis();
+  let tracked_receipt = match tokio::time::timeout(receipt_timeout, tracked_receipt).await {
+    Ok(Ok(tracked_receipt)) => tracked_receipt,
+    Ok(Err(_)) => {
+      return Err(SendError::new(
+        SendErrorCategory::Other,
+        index,
+        format!("wait for receipt failed, tx: {:?}", transaction_hash),
+      ))
+    }
+    Err(_) => {
+      tracker.untrack(&transaction_hash);
+      return Err(SendError::new(
+        SendErrorCategory::ReceiptTimeout,
+        index,
+        format!(
+          "no receipt after {}ms, tx: {:?}",
+          receipt_timeout.as_millis(),
+          transaction_hash
+        ),
+      ));
+    }
+  };
 
-  let send_time_cost = send_time - start_time;
+  let transaction_receipt = tracked_receipt.receipt;
+  let receipt = Receipt {
+    hash: format!(
+      "0x{:}",
+      transaction_receipt.transaction_hash.encode_hex::<String>()
+    ),
+    start_time: clock.unix_millis(start_time),
+    send_time: clock.unix_millis(send_time),
+    send_time_cost: millis_between(start_time, send_time),
+    response_time_cost: millis_between(send_time, tracked_receipt.observed_at),
+    block_number: transaction_receipt
+      .block_number
+      .unwrap_or_default()
+      .to_string(),
+    block_timestamp: tracked_receipt.block_timestamp.to_string(),
+    transaction_index: transaction_receipt.transaction_index.as_u32(),
+    success: transaction_receipt.status.is_some_and(|x| x.as_u32() == 1),
+    from: format!("0x{:}", transaction_receipt.from.encode_hex::<String>()),
+    nonce: nonce.as_u64() as i64,
+    tx_type: transaction_receipt
+      .transaction_type
+      .map_or(tx_type, |x| x.as_u32()),
+    gas_used: transaction_receipt.gas_used.unwrap_or_default().to_string(),
+    cumulative_gas_used: transaction_receipt.cumulative_gas_used.to_string(),
+    effective_gas_price: transaction_receipt
+      .effective_gas_price
+      .unwrap_or_default()
+      .to_string(),
+  };
+  Ok(receipt)
+}
 
-  let receipt = pending_tx
-    .await
-    .with_context(|| format!("wait for receipt failed, tx: {}", transaction_hash))?;
-
-  let response_time_cost = SystemTime::now()
-    .duration_since(UNIX_EPOCH)
-    .with_context(|| "Failed to get duration since epoch".to_string())?
-    .as_millis()
-    - send_time;
-
-  if let Some(transaction_receipt) = receipt {
-    let receipt = Receipt {
-      hash: format!(
-        "0x{:}",
-        transaction_receipt.transaction_hash.encode_hex::<String>()
-      ),
-      start_time: start_time.to_string(),
-      send_time: send_time.to_string(),
-      send_time_cost: send_time_cost.to_string(),
-      response_time_cost: response_time_cost.to_string(),
-      block_number: transaction_receipt
-        .block_number
-        .unwrap_or_default()
-        .to_string(),
-      success: transaction_receipt
-        .status
-        .map_or(false, |x| x.as_u32() == 1),
-    };
-    Ok(Some(receipt))
-  } else {
-    Ok(None)
+// eip-2718 type of a signed raw transaction, legacy transactions start with a rlp list prefix
+fn raw_transaction_type(raw: &[u8]) -> u32 {
+  match raw.first() {
+    Some(&first) if first < 0xc0 => first as u32,
+    _ => 0,
+  }
+}
+
+// the hash of a signed transaction is the hash of its raw bytes, for legacy and typed txs, except
+// for the network form of blob transactions which hashes the payload without the sidecar
+fn raw_transaction_hash(raw: &[u8]) -> H256 {
+  if raw_transaction_type(raw) == 3 {
+    if let Ok(fields) = Rlp::new(&raw[1..]).at(0) {
+      if fields.is_list() {
+        let mut payload = vec![3u8];
+        payload.extend_from_slice(fields.as_raw());
+        return keccak256(payload).into();
+      }
+    }
   }
+  keccak256(raw).into()
+}
+
+// nonce of a signed raw transaction, for legacy and typed (eip-2718) envelopes
+fn raw_transaction_nonce(raw: &[u8]) -> Result<U256> {
+  let nonce = match raw_transaction_type(raw) {
+    0 => Rlp::new(raw).val_at(0),
+    tx_type => {
+      let payload = Rlp::new(&raw[1..]);
+      // the network form of blob transactions wraps the payload with blobs, commitments and proofs
+      let is_wrapped = tx_type == 3 && payload.at(0).is_ok_and(|x| x.is_list());
+      let fields = if is_wrapped {
+        payload.at(0)
+      } else {
+        Ok(payload)
+      };
+      // chain id goes first in every typed payload
+      fields.and_then(|fields| fields.val_at(1))
+    }
+  };
+
+  nonce.with_context(|| "Failed to decode nonce of raw transaction".to_string())
 }
 
 pub async fn send_raw_transactions(
   http_provider: String,
   transactions: Vec<String>,
-) -> Vec<Result<Option<Receipt>>> {
-  let mut futures = Vec::new();
+  options: SendOptions,
+) -> Result<Vec<std::result::Result<Receipt, SendError>>> {
+  // one provider (and connection pool) shared by every task of the round
+  let provider = connect_provider(&http_provider, &options.connection.unwrap_or_default())
+    .await
+    .with_context(|| "Failed to create provider".to_string())?;
 
-  for (i, tx) in transactions.iter().enumerate() {
-    let task = send_raw_transaction_inner(http_provider.clone(), tx.clone(), i);
-    futures.push(task);
-  }
+  let rate_control_options = options.rate_control.unwrap_or_default();
+  let schedule = RateControl::from_options(&rate_control_options)
+    .with_context(|| "Invalid rate control".to_string())?
+    .schedule(transactions.len());
+  let in_flight = rate_control_options
+    .max_in_flight
+    .map(|max| Arc::new(Semaphore::new(max.max(1) as usize)));
+
+  let receipt_timeout = options
+    .receipt_timeout_ms
+    .map_or(DEFAULT_RECEIPT_TIMEOUT, |ms| {
+      Duration::from_millis(ms as u64)
+    });
+  let poll_interval = options
+    .poll_interval_ms
+    .map_or(DEFAULT_BLOCK_POLL_INTERVAL, |ms| {
+      Duration::from_millis(ms.max(1) as u64)
+    });
+
+  // one block follower resolves the receipts of the whole round
+  let tracker = Arc::new(
+    ReceiptTracker::start(
+      provider.clone(),
+      poll_interval,
+      options.confirmations.unwrap_or(1) as u64,
+    )
+    .await
+    .with_context(|| "Failed to start receipt tracker".to_string())?,
+  );
+
+  let mut handles: Vec<task::JoinHandle<_>> = Vec::with_capacity(transactions.len());
+  let clock = Clock::new();
+  let round_start = Instant::now();
 
-  let mut handles: Vec<task::JoinHandle<_>> = Vec::with_capacity(futures.len());
+  for (i, (tx, offset)) in transactions.into_iter().zip(schedule).enumerate() {
+    tokio::time::sleep_until(round_start + offset).await;
 
-  for fut in futures {
-    handles.push(tokio::spawn(fut));
+    // the permit is held until the receipt arrives
+    let permit = match &in_flight {
+      Some(semaphore) => Some(
+        semaphore
+          .clone()
+          .acquire_owned()
+          .await
+          .with_context(|| "In-flight semaphore closed".to_string())?,
+      ),
+      None => None,
+    };
+
+    let provider = provider.clone();
+    let tracker = tracker.clone();
+    handles.push(tokio::spawn(async move {
+      let result =
+        send_raw_transaction_inner(provider, tracker, clock, receipt_timeout, tx, i).await;
+      drop(permit);
+      result
+    }));
   }
 
   let mut results = Vec::with_capacity(handles.len());
@@ -106,5 +529,158 @@ pub async fn send_raw_transactions(
     results.push(handle.await.unwrap());
   }
 
-  results
+  Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use ethers::types::{H256, U256};
+
+  use crate::transaction::{
+    raw_transaction_hash, raw_transaction_nonce, raw_transaction_type, RateControl,
+    RateControlOptions, SendErrorCategory,
+  };
+
+  #[test]
+  fn test_send_error_category_from_message() {
+    let cases = [
+      ("nonce too low", Some(SendErrorCategory::NonceTooLow)),
+      (
+        "replacement transaction underpriced",
+        Some(SendErrorCategory::ReplacementUnderpriced),
+      ),
+      (
+        "insufficient funds for gas * price + value",
+        Some(SendErrorCategory::InsufficientFunds),
+      ),
+      ("txpool is full", Some(SendErrorCategory::TxpoolFull)),
+      ("already known", Some(SendErrorCategory::AlreadyKnown)),
+      ("execution reverted", None),
+    ];
+
+    for (message, category) in cases {
+      assert_eq!(SendErrorCategory::from_message(message), category);
+    }
+  }
+
+  #[test]
+  fn test_send_error_category_from_name() {
+    for category in SendErrorCategory::ALL {
+      assert_eq!(SendErrorCategory::from_name(category.as_str()), category);
+    }
+    assert_eq!(
+      SendErrorCategory::from_name("unknown"),
+      SendErrorCategory::Other
+    );
+  }
+
+  #[test]
+  fn test_raw_transaction_nonce() {
+    // legacy, nonce 9, from eip-155
+    let legacy = hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap();
+    assert_eq!(raw_transaction_type(&legacy), 0);
+    assert_eq!(raw_transaction_nonce(&legacy).unwrap(), U256::from(9));
+
+    // eip-1559, chain id 1, nonce 3 (unsigned fields are enough to read the nonce)
+    let typed = [&[0x02u8][..], &hex::decode("c80103808080825208").unwrap()].concat();
+    assert_eq!(raw_transaction_type(&typed), 2);
+    assert_eq!(raw_transaction_nonce(&typed).unwrap(), U256::from(3));
+  }
+
+  #[test]
+  fn test_raw_transaction_hash() {
+    let typed = [&[0x02u8][..], &hex::decode("c80103808080825208").unwrap()].concat();
+    assert_eq!(
+      raw_transaction_hash(&typed),
+      H256::from(ethers::utils::keccak256(&typed))
+    );
+
+    // blob network form, the sidecar (here empty blobs, commitments and proofs) isn't hashed
+    let payload = hex::decode("c80103808080825208").unwrap();
+    let wrapped = [
+      &[0x03u8, 0xcc][..],
+      &payload,
+      &hex::decode("c0c0c0").unwrap(),
+    ]
+    .concat();
+    assert_eq!(raw_transaction_nonce(&wrapped).unwrap(), U256::from(3));
+    assert_eq!(
+      raw_transaction_hash(&wrapped),
+      H256::from(ethers::utils::keccak256([&[0x03u8][..], &payload].concat()))
+    );
+  }
+
+  #[test]
+  fn test_fixed_load_schedule() {
+    let schedule = RateControl::FixedLoad { tps: 4.0 }.schedule(3);
+
+    assert_eq!(
+      schedule,
+      vec![
+        Duration::ZERO,
+        Duration::from_millis(250),
+        Duration::from_millis(500)
+      ]
+    );
+  }
+
+  #[test]
+  fn test_maximum_rate_schedule() {
+    let schedule = RateControl::MaximumRate {
+      start_tps: 1.0,
+      end_tps: 4.0,
+    }
+    .schedule(3);
+
+    // 1 tps, then 2.5 tps, then 4 tps
+    assert_eq!(
+      schedule,
+      vec![
+        Duration::ZERO,
+        Duration::from_secs(1),
+        Duration::from_millis(1400)
+      ]
+    );
+  }
+
+  #[test]
+  fn test_rate_control_from_options() {
+    assert_eq!(
+      RateControl::from_options(&RateControlOptions::default()).unwrap(),
+      RateControl::Unlimited
+    );
+    assert!(RateControl::from_options(&RateControlOptions {
+      mode: Some("fixed-load".to_string()),
+      tps: Some(0.0),
+      ..Default::default()
+    })
+    .is_err());
+    assert_eq!(
+      RateControl::from_options(&RateControlOptions {
+        mode: Some("maximum-rate".to_string()),
+        tps: Some(4.0),
+        start_tps: Some(1.0),
+        ..Default::default()
+      })
+      .unwrap(),
+      RateControl::MaximumRate {
+        start_tps: 1.0,
+        end_tps: 4.0
+      }
+    );
+    assert!(RateControl::from_options(&RateControlOptions {
+      mode: Some("maximum-rate".to_string()),
+      tps: Some(4.0),
+      ..Default::default()
+    })
+    .is_err());
+    assert!(RateControl::from_options(&RateControlOptions {
+      mode: Some("fixed-rate".to_string()),
+      tps: Some(4.0),
+      ..Default::default()
+    })
+    .is_err());
+  }
 }
@@ -0,0 +1,311 @@
+// runs a coordinator and its workers as separate cli processes over tcp, against a fake node
+use std::collections::HashSet;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ethers::signers::{coins_bip39::English, MnemonicBuilder, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, H256};
+use ethers::utils::{keccak256, rlp::Rlp};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::{Child, Command};
+
+const MNEMONIC: &str = "test test test test test test test test test test test junk";
+const CHAIN_ID: u64 = 31337;
+const WORKERS: u32 = 3;
+
+// a chain mining the transactions received since the last `eth_blockNumber` into a new block.
+// Transactions from the `rejected` accounts fail with nonce too low
+#[derive(Default)]
+struct FakeNode {
+  rejected: HashSet<Address>,
+  blocks: Vec<Vec<(H256, Address)>>,
+  pending: Vec<(H256, Address)>,
+}
+
+impl FakeNode {
+  fn handle(&mut self, method: &str, params: &Value) -> Result<Value, (i64, &'static str)> {
+    match method {
+      "eth_blockNumber" => {
+        if self.blocks.is_empty() || !self.pending.is_empty() {
+          self.blocks.push(std::mem::take(&mut self.pending));
+        }
+        Ok(json!(format!("{:#x}", self.blocks.len() - 1)))
+      }
+      "eth_sendRawTransaction" => {
+        let raw = hex::decode(params[0].as_str().unwrap().trim_start_matches("0x")).unwrap();
+        let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        let from = signature.recover(tx.sighash()).unwrap();
+        if self.rejected.contains(&from) {
+          return Err((-32000, "nonce too low"));
+        }
+        let hash = H256::from(keccak256(&raw));
+        self.pending.push((hash, from));
+        Ok(json!(hash))
+      }
+      "eth_getBlockByNumber" => Ok(self.block(params).map_or(Value::Null, |(number, txs)| {
+        block_json(number, txs.iter().map(|(hash, _)| *hash).collect())
+      })),
+      "eth_getBlockReceipts" => Ok(self.block(params).map_or(Value::Null, |(number, txs)| {
+        let receipts: Vec<Value> = txs
+          .iter()
+          .enumerate()
+          .map(|(i, (hash, from))| receipt_json(number, i, *hash, *from))
+          .collect();
+        json!(receipts)
+      })),
+      _ => Err((-32601, "method not found")),
+    }
+  }
+
+  fn block(&self, params: &Value) -> Option<(usize, &Vec<(H256, Address)>)> {
+    let number = params[0].as_str()?.trim_start_matches("0x");
+    let number = usize::from_str_radix(number, 16).ok()?;
+    Some((number, self.blocks.get(number)?))
+  }
+}
+
+fn block_json(number: usize, transactions: Vec<H256>) -> Value {
+  json!({
+    "hash": H256::from_low_u64_be(number as u64 + 1),
+    "parentHash": H256::from_low_u64_be(number as u64),
+    "sha3Uncles": H256::zero(),
+    "miner": Address::zero(),
+    "stateRoot": H256::zero(),
+    "transactionsRoot": H256::zero(),
+    "receiptsRoot": H256::zero(),
+    "number": format!("{:#x}", number),
+    "gasUsed": format!("{:#x}", transactions.len() * 21000),
+    "gasLimit": "0x1c9c380",
+    "extraData": "0x",
+    "logsBloom": format!("0x{}", "00".repeat(256)),
+    "timestamp": format!("{:#x}", 1_700_000_000 + number),
+    "difficulty": "0x0",
+    "totalDifficulty": "0x0",
+    "uncles": [],
+    "transactions": transactions,
+    "size": "0x0",
+    "mixHash": H256::zero(),
+    "nonce": "0x0000000000000000",
+    "baseFeePerGas": "0x1",
+  })
+}
+
+fn receipt_json(number: usize, index: usize, hash: H256, from: Address) -> Value {
+  json!({
+    "transactionHash": hash,
+    "transactionIndex": format!("{:#x}", index),
+    "blockHash": H256::from_low_u64_be(number as u64 + 1),
+    "blockNumber": format!("{:#x}", number),
+    "from": from,
+    "to": Address::zero(),
+    "cumulativeGasUsed": format!("{:#x}", (index + 1) * 21000),
+    "gasUsed": "0x5208",
+    "contractAddress": null,
+    "logs": [],
+    "status": "0x1",
+    "logsBloom": format!("0x{}", "00".repeat(256)),
+    "type": "0x0",
+    "effectiveGasPrice": "0x1",
+  })
+}
+
+// json-rpc over keep-alive http/1.1, one request per http request
+async fn serve_connection(stream: TcpStream, node: Arc<Mutex<FakeNode>>) -> std::io::Result<()> {
+  let mut stream = BufReader::new(stream);
+  loop {
+    let mut content_length = 0;
+    loop {
+      let mut line = String::new();
+      if stream.read_line(&mut line).await? == 0 {
+        return Ok(());
+      }
+      let line = line.trim_end();
+      if line.is_empty() {
+        break;
+      }
+      if let Some((name, value)) = line.split_once(':') {
+        if name.eq_ignore_ascii_case("content-length") {
+          content_length = value.trim().parse().unwrap();
+        }
+      }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await?;
+    let request: Value = serde_json::from_slice(&body).unwrap();
+    let result = node
+      .lock()
+      .unwrap()
+      .handle(request["method"].as_str().unwrap(), &request["params"]);
+    let response = match result {
+      Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+      Err((code, message)) => json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "error": { "code": code, "message": message },
+      }),
+    };
+
+    let response = response.to_string();
+    let head = format!(
+      "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+      response.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.as_bytes()).await?;
+  }
+}
+
+async fn start_node(node: FakeNode) -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let address = listener.local_addr().unwrap();
+  let node = Arc::new(Mutex::new(node));
+  tokio::spawn(async move {
+    loop {
+      let (stream, _) = listener.accept().await.unwrap();
+      tokio::spawn(serve_connection(stream, node.clone()));
+    }
+  });
+  format!("http://{}", address)
+}
+
+fn cli(config: &str, args: &[&str], logs: Stdio) -> Child {
+  Command::new(env!("CARGO_BIN_EXE_rs-addon"))
+    .args(["-c", config])
+    .args(args)
+    .stdout(logs)
+    .stderr(Stdio::null())
+    .kill_on_drop(true)
+    .spawn()
+    .unwrap()
+}
+
+// waits for `pattern` in the logs of `child`, then keeps draining them so it never blocks on a
+// full pipe
+async fn wait_for_log(child: &mut Child, pattern: &str) {
+  let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+  while let Some(line) = stdout.next_line().await.unwrap() {
+    if line.contains(pattern) {
+      tokio::spawn(async move { while let Ok(Some(_)) = stdout.next_line().await {} });
+      return;
+    }
+  }
+  panic!("exited before logging {:?}", pattern);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_coordinator_and_worker_processes() {
+  // benchmark accounts divisible by 5 are rejected
+  let rejected = (5..=30u32)
+    .step_by(5)
+    .map(|index| {
+      let wallet = MnemonicBuilder::<English>::default()
+        .phrase(MNEMONIC)
+        .index(index)
+        .unwrap()
+        .build()
+        .unwrap();
+      wallet.address()
+    })
+    .collect();
+  let node_url = start_node(FakeNode {
+    rejected,
+    ..Default::default()
+  })
+  .await;
+
+  let dir = std::env::temp_dir().join(format!("rs-addon-cluster-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let config = dir.join("config.json");
+  let stats = dir.join("stats.jsonl");
+  // 10 transactions per worker, in rounds of 4, 4 and 2
+  let content = json!({
+    "network": {
+      "evm_type": "ethereum",
+      "node_url": node_url,
+      "gas_limit": "21000",
+      "gas_price": "1000000000",
+      "benchmark": true,
+    },
+    "account": {
+      "mnemonic": MNEMONIC,
+      "l1_holding": "1",
+      "optimism_holding": "1",
+      "zksync_holding": "1",
+      "random_mnemonic": MNEMONIC,
+    },
+    "tx_type": "normalTransfer",
+    "rate_control": { "txs_per_batch": 4, "every_n_second": 1 },
+    "total_tx": 10,
+  });
+  std::fs::write(&config, content.to_string()).unwrap();
+  let config = config.to_string_lossy().to_string();
+
+  let port = std::net::TcpListener::bind("127.0.0.1:0")
+    .unwrap()
+    .local_addr()
+    .unwrap()
+    .port();
+  let address = format!("127.0.0.1:{}", port);
+  let mut coordinator = cli(
+    &config,
+    &[
+      "coordinator",
+      "--listen",
+      &address,
+      "--workers",
+      &WORKERS.to_string(),
+      "--stats",
+      &stats.to_string_lossy(),
+    ],
+    Stdio::piped(),
+  );
+  wait_for_log(&mut coordinator, "waiting for").await;
+
+  let chain_id = CHAIN_ID.to_string();
+  let mut workers: Vec<Child> = (0..WORKERS)
+    .map(|_| {
+      let args = ["worker", &address, "--chain-id", &chain_id];
+      cli(&config, &args, Stdio::null())
+    })
+    .collect();
+
+  let status = tokio::time::timeout(Duration::from_secs(60), coordinator.wait())
+    .await
+    .expect("coordinator timed out")
+    .unwrap();
+  assert!(status.success());
+  for worker in workers.iter_mut() {
+    let status = tokio::time::timeout(Duration::from_secs(10), worker.wait())
+      .await
+      .expect("worker timed out")
+      .unwrap();
+    assert!(status.success());
+  }
+
+  let rounds: Vec<Value> = std::fs::read_to_string(&stats)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str(line).unwrap())
+    .collect();
+  std::fs::remove_dir_all(&dir).unwrap();
+
+  // workers own accounts 1 - 10, 11 - 20 and 21 - 30: round 0 sends 1 - 4, 11 - 14 and 21 - 24,
+  // round 1 5 - 8, 15 - 18 and 25 - 28, round 2 9 - 10, 19 - 20 and 29 - 30
+  let counts: Vec<(u64, u64, u64)> = rounds
+    .iter()
+    .map(|round| {
+      let count = |name: &str| round[name].as_u64().unwrap();
+      (count("total"), count("success"), count("failed"))
+    })
+    .collect();
+  assert_eq!(counts, vec![(12, 12, 0), (12, 9, 3), (6, 3, 3)]);
+  assert_eq!(
+    rounds[1]["failures"],
+    json!([{ "category": "nonce_too_low", "count": 3 }])
+  );
+}